@@ -1,7 +1,8 @@
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::{fs::File, path::Path};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 
+use delatin::export::write_obj;
 use delatin::{triangulate, Error};
 
 fn main() {
@@ -12,20 +13,8 @@ fn main() {
     let heights: Vec<f64> = serde_json::from_reader(file).unwrap();
     let (points, triangles) = triangulate(&heights, (width, height), Error(1.0)).unwrap();
 
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open("./conversion/output.obj")
-        .unwrap();
-
-    for point in points {
-        let original_index_a = point.1 * width + point.0;
-        let height = heights[original_index_a];
-        writeln!(file, "v {} {} {}", point.0, point.1, height).unwrap();
-    }
-
-    for (a, b, c) in triangles {
-        writeln!(file, "f {} {} {}", a + 1, b + 1, c + 1).unwrap();
-    }
+    let file = File::create("./conversion/output.obj").unwrap();
+    let mut writer = BufWriter::new(file);
+    write_obj(&mut writer, &points, &triangles, &heights, width).unwrap();
+    writer.flush().unwrap();
 }
@@ -0,0 +1,50 @@
+#![no_main]
+
+use delatin::predicates::signed_area;
+use delatin::{triangulate_mesh, Error, Mesh};
+use libfuzzer_sys::fuzz_target;
+
+// first three bytes are width, height and max error, the rest is reinterpreted as heights
+fuzz_target!(|data: &[u8]| {
+    let [width, height, max_error, height_bytes @ ..] = data else {
+        return;
+    };
+    let width = *width as usize;
+    let height = *height as usize;
+    let max_error = Error(*max_error as f64 / 16.0);
+    let heights = height_bytes
+        .chunks_exact(8)
+        .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect::<Vec<f64>>();
+
+    // must never panic, only return errors
+    let Ok(mesh) = triangulate_mesh(&heights, (width, height), max_error) else {
+        return;
+    };
+    let points = mesh.points();
+    let triangles = mesh.triangles_flat();
+    let half_edges = mesh.half_edges();
+
+    for point in points {
+        assert!(point.0 < width && point.1 < height);
+    }
+    for &vertex in triangles {
+        assert!(vertex < points.len());
+    }
+    for triangle in triangles.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| points[triangle[i]]);
+        assert!(signed_area(a, b, c) > 0, "{:?} has no positive area", (a, b, c));
+    }
+    // twins point back and run between the same vertices in the opposite direction
+    let next = |edge: usize| edge - edge % 3 + (edge + 1) % 3;
+    for (edge, twin) in half_edges.iter().enumerate() {
+        if let Some(twin) = *twin {
+            assert_eq!(half_edges[twin], Some(edge));
+            assert_eq!(triangles[edge], triangles[next(twin)]);
+            assert_eq!(triangles[next(edge)], triangles[twin]);
+        }
+    }
+    // triangles must not overlap
+    let rebuilt = Mesh::from_triangles(points.to_vec(), &mesh.triangles());
+    assert_eq!(rebuilt.validate(), Ok(()));
+});
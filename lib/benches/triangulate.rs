@@ -0,0 +1,52 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use delatin::{triangulate, Error};
+use std::{fs::File, path::Path};
+
+const FIXTURE_SIZE: usize = 512;
+const SIZES: [usize; 3] = [128, 256, 512];
+const MAX_ERRORS: [f64; 3] = [0.2, 1.0, 5.0];
+
+/// Loads the 512x512 heights fixture shared with the `test` crate.
+fn load_fixture() -> Vec<f64> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../data/input.json");
+    let file = File::open(path).unwrap();
+    let heights: Vec<f64> = serde_json::from_reader(file).unwrap();
+    assert_eq!(heights.len(), FIXTURE_SIZE * FIXTURE_SIZE);
+
+    heights
+}
+
+/// Top left `size` by `size` corner of the fixture.
+fn crop(heights: &[f64], size: usize) -> Vec<f64> {
+    heights
+        .chunks_exact(FIXTURE_SIZE)
+        .take(size)
+        .flat_map(|row| &row[..size])
+        .copied()
+        .collect()
+}
+
+fn bench_triangulate(criterion: &mut Criterion) {
+    let fixture = load_fixture();
+    let mut group = criterion.benchmark_group("triangulate");
+    group.sample_size(20);
+
+    for size in SIZES {
+        let heights = crop(&fixture, size);
+        group.throughput(Throughput::Elements((size * size) as u64));
+        for max_error in MAX_ERRORS {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{size}x{size}"), max_error),
+                &heights,
+                |bencher, heights| {
+                    bencher.iter(|| triangulate(heights, (size, size), Error(max_error)).unwrap())
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_triangulate);
+criterion_main!(benches);
@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag for aborting a triangulation, e.g. from a UI thread when parameters change.
+///
+/// Clones share the same flag, so a clone can be passed to
+/// [`TriangulationOptions::cancellation`](crate::TriangulationOptions::cancellation) and the
+/// original cancelled from another thread. The flag is checked once per refinement step with
+/// a relaxed atomic load, which doesn't slow down triangulations that are not cancelled.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{CancellationToken, Error, TriangulationError, TriangulationOptions};
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+///     .collect::<Vec<f64>>();
+///
+/// let token = CancellationToken::new();
+/// let options = TriangulationOptions::new()
+///     .max_error(Error(0.1))
+///     .cancellation(token.clone());
+/// let result = options.run_with_progress(&heights, 33, 33, |_, vertex_count| {
+///     if vertex_count == 20 {
+///         token.cancel();
+///     }
+/// });
+///
+/// let Err(TriangulationError::Cancelled { partial }) = result else {
+///     panic!("triangulation is not cancelled");
+/// };
+/// assert_eq!(partial.points().len(), 20);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation, triangulations using this token stop before their next step.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks whether cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Tokens are equal when they share the same flag.
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancellationToken {
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
@@ -1,23 +1,66 @@
-use std::fmt;
+use std::io;
 
-#[derive(Debug)]
+use crate::{Error, Mesh};
+
+/// Error of the triangulation or of reading and writing its data.
+///
+/// IO errors of reading heights and of exporters convert into [`TriangulationError::Io`], so
+/// they can be used with `?` next to triangulation. The IO error is its
+/// [`source`](std::error::Error::source) rather than a part of the message, so reports
+/// walking the chain don't print it twice.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate, Error, TriangulationError};
+///
+/// fn to_obj(heights: &[f64]) -> Result<Vec<u8>, TriangulationError> {
+///     let (points, triangles) = triangulate(heights, (2, 2), Error(1.0))?;
+///     let mut obj = Vec::new();
+///     delatin::export::write_obj(&mut obj, &points, &triangles, heights, 2)?;
+///
+///     Ok(obj)
+/// }
+///
+/// assert!(!to_obj(&[100.1, 123.4, 111.5, 121.4])?.is_empty());
+/// # Ok::<(), TriangulationError>(())
+/// ```
+#[derive(Debug, thiserror::Error)]
 pub enum TriangulationError {
+    #[error("No max error in queue.")]
     MaxErrorRetrievalError,
+    #[error("Priority queue is empty.")]
     EmptyQueueError,
+    #[error("Length of heights data is not equal to width * height.")]
     InvalidDataLengthError,
+    #[error("Heights data ended in the middle of a row.")]
+    IncompleteRowError,
+    #[error("Row {row} is not as long as the first row.")]
+    InvalidRowLength { row: usize },
+    #[error("Mesh doesn't fit the grid.")]
+    InvalidMeshError,
+    #[error("Region is empty or doesn't fit the grid.")]
+    InvalidRegionError,
+    #[error("Image is not 8-bit or 16-bit grayscale.")]
+    UnsupportedImageError,
+    #[error("Height at index {index} is not finite.")]
+    NonFiniteHeight { index: usize },
+    #[error("Cell size is not positive and finite.")]
+    InvalidCellSizeError,
+    #[error("Seed point doesn't fit the grid.")]
+    InvalidSeedPointError,
+    #[error("Breakline endpoint doesn't fit the grid.")]
+    InvalidBreaklineError,
+    #[error("Grid {width}x{height} is less than 2 pixels wide or high.")]
+    GridTooSmall { width: usize, height: usize },
+    #[error("Grid {width}x{height} has too many pixels for u32 indices.")]
+    GridTooLarge { width: usize, height: usize },
+    #[error("Confidence at index {index} is negative or not finite.")]
+    InvalidConfidence { index: usize },
+    #[error("Triangulation was cancelled.")]
+    Cancelled { partial: Box<Mesh> },
+    #[error("Max error {requested} is above the achieved {achieved}, refinement can't be undone.")]
+    MaxErrorIncreased { requested: Error, achieved: Error },
+    #[error("Failed to read or write data.")]
+    Io(#[from] io::Error),
 }
-
-impl fmt::Display for TriangulationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            TriangulationError::MaxErrorRetrievalError => write!(f, "No max error in queue."),
-            TriangulationError::EmptyQueueError => write!(f, "Priority queue is empty."),
-            TriangulationError::InvalidDataLengthError => write!(
-                f,
-                "Length of heights data is not equal to width * height."
-            ),
-        }
-    }
-}
-
-impl std::error::Error for TriangulationError {}
@@ -0,0 +1,1077 @@
+/*!
+Writers for common mesh formats.
+
+Heights of vertices are read from the grid, which can be of any [`HeightValue`] type.
+*/
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use crate::utils::compute_vertex_normals;
+#[cfg(feature = "quantized-mesh")]
+use crate::Mesh;
+use crate::{HeightValue, Point, Triangle};
+
+/// Mesh file format supported by exporters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Wavefront OBJ, see [`write_obj`].
+    Obj,
+    /// Binary STL, see [`write_stl_binary`].
+    StlBinary,
+    /// ASCII PLY, see [`write_ply`].
+    PlyAscii,
+    /// Binary little-endian PLY, see [`write_ply`].
+    PlyBinary,
+}
+
+impl ExportFormat {
+    /// Writes mesh to the writer in this format.
+    pub fn write<H: HeightValue, W: Write>(
+        &self,
+        writer: W,
+        points: &[Point],
+        triangles: &[Triangle],
+        height_data: &[H],
+        width: usize,
+    ) -> io::Result<()> {
+        match self {
+            ExportFormat::Obj => write_obj(writer, points, triangles, height_data, width),
+            ExportFormat::StlBinary => {
+                write_stl_binary(writer, points, triangles, height_data, width, 0.0)
+            }
+            ExportFormat::PlyAscii => {
+                write_ply(writer, points, triangles, height_data, width, true, 0.0)
+            }
+            ExportFormat::PlyBinary => {
+                write_ply(writer, points, triangles, height_data, width, false, 0.0)
+            }
+        }
+    }
+
+    /// Number of bytes mesh takes in this format, without keeping the output in memory.
+    pub(crate) fn size<H: HeightValue>(
+        &self,
+        points: &[Point],
+        triangles: &[Triangle],
+        height_data: &[H],
+        width: usize,
+    ) -> usize {
+        let mut counter = ByteCounter(0);
+        self.write(&mut counter, points, triangles, height_data, width)
+            .expect("Writing to byte counter can't fail.");
+
+        counter.0
+    }
+}
+
+/// Optional data written by [`write_obj_with_options`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ObjOptions {
+    /// Write smooth per-vertex normals as `vn` lines, pointing up.
+    pub normals: bool,
+    /// Write texture coordinates as `vt u v` lines, with `u = x / (width - 1)` and
+    /// `v = y / (height - 1)`, so the texture origin is at the first pixel of the grid.
+    pub uvs: bool,
+    /// Flip texture coordinates to `v = 1 - y / (height - 1)`, for renderers with the texture
+    /// origin at the bottom-left corner like OpenGL. The first grid row is then at the top
+    /// of the texture, same as [`atlas_uvs`].
+    pub flip_v: bool,
+    /// Map texture coordinates into a cell of a shared atlas given as `(cols, rows, index)`,
+    /// instead of the whole texture. Cells are numbered in row-major order from the top-left
+    /// one, same as in [`atlas_uvs`], regardless of `flip_v`.
+    pub uv_tile: Option<(usize, usize, usize)>,
+    /// Constant added to every height before writing it, e.g. a datum shift. Heights are not
+    /// scaled or normalized, so `z` of a vertex is its grid sample plus the offset.
+    pub z_offset: f64,
+}
+
+/// Writes mesh in Wavefront OBJ format.
+///
+/// Every point is written as `v x y z` line with its height looked up in the grid,
+/// every triangle as `f a b c` line with 1-based indices into points.
+///
+/// # Arguments
+///
+/// * `writer` - Destination of the OBJ text.
+/// * `points` - Vertices of the mesh.
+/// * `triangles` - Triangles of the mesh, each defined by indices into the `points`.
+/// * `height_data` - Height values of the grid the mesh was built from.
+/// * `width` - The width of the grid.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::write_obj;
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let points = vec![(0, 0), (1, 0), (1, 1), (0, 1)];
+/// let triangles = vec![(2, 0, 3), (0, 2, 1)];
+///
+/// let mut obj = Vec::new();
+/// write_obj(&mut obj, &points, &triangles, &heights, 2).unwrap();
+///
+/// assert_eq!(
+///     String::from_utf8(obj).unwrap(),
+///     "v 0 0 100.1\n\
+///      v 1 0 123.4\n\
+///      v 1 1 121.4\n\
+///      v 0 1 111.5\n\
+///      f 3 1 4\n\
+///      f 1 3 2\n"
+/// );
+/// ```
+pub fn write_obj<H: HeightValue, W: Write>(
+    writer: W,
+    points: &[Point],
+    triangles: &[Triangle],
+    height_data: &[H],
+    width: usize,
+) -> io::Result<()> {
+    write_obj_with_options(
+        writer,
+        points,
+        triangles,
+        height_data,
+        width,
+        &ObjOptions::default(),
+    )
+}
+
+/// Writes mesh in Wavefront OBJ format with optional data.
+///
+/// Same as [`write_obj`], but with normals every point also gets `vn x y z` line
+/// and faces reference them as `f a//a b//b c//c`. With texture coordinates every point
+/// gets `vt u v` line and faces reference them as `f a/a b/b c/c`, or `f a/a/a b/b/b c/c/c`
+/// together with normals. Grids one pixel wide or high get zero `u` or `v`.
+///
+/// # Arguments
+///
+/// * `writer` - Destination of the OBJ text.
+/// * `points` - Vertices of the mesh.
+/// * `triangles` - Triangles of the mesh, each defined by indices into the `points`.
+/// * `height_data` - Height values of the grid the mesh was built from.
+/// * `width` - The width of the grid.
+/// * `options` - Optional data to write.
+///
+/// # Panics
+///
+/// Panics if `uv_tile` is set with texture coordinates and its atlas has no cells
+/// or its index is not less than `cols * rows`.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::{write_obj_with_options, ObjOptions};
+/// use delatin::{triangulate, Error};
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let (points, triangles) = triangulate(&heights, (2, 2), Error(1.0))?;
+///
+/// let mut obj = Vec::new();
+/// let options = ObjOptions { normals: true, ..Default::default() };
+/// write_obj_with_options(&mut obj, &points, &triangles, &heights, 2, &options).unwrap();
+/// let obj = String::from_utf8(obj).unwrap();
+///
+/// let vertices = obj.lines().filter(|line| line.starts_with("v ")).count();
+/// let normals = obj.lines().filter(|line| line.starts_with("vn ")).count();
+/// assert_eq!(normals, vertices);
+/// for face in obj.lines().filter(|line| line.starts_with("f ")) {
+///     for corner in face.split_whitespace().skip(1) {
+///         let (vertex, normal) = corner.split_once("//").unwrap();
+///         assert_eq!(vertex, normal);
+///     }
+/// }
+///
+/// // point (1, 0) is the second vertex
+/// for (flip_v, uv) in [(false, "vt 1 0"), (true, "vt 1 1")] {
+///     let mut obj = Vec::new();
+///     let options = ObjOptions { uvs: true, flip_v, ..Default::default() };
+///     write_obj_with_options(&mut obj, &points, &triangles, &heights, 2, &options).unwrap();
+///     let obj = String::from_utf8(obj).unwrap();
+///
+///     assert_eq!(obj.lines().filter(|line| line.starts_with("vt ")).nth(1), Some(uv));
+///     assert!(obj.lines().any(|line| line.starts_with("f ") && line.contains("2/2")));
+/// }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn write_obj_with_options<H: HeightValue, W: Write>(
+    mut writer: W,
+    points: &[Point],
+    triangles: &[Triangle],
+    height_data: &[H],
+    width: usize,
+    options: &ObjOptions,
+) -> io::Result<()> {
+    for point in points {
+        let height = height_data[point.1 * width + point.0].to_f64() + options.z_offset;
+        writeln!(writer, "v {} {} {}", point.0, point.1, height)?;
+    }
+
+    if options.uvs {
+        let height = height_data.len().checked_div(width).unwrap_or(0);
+        let uv_tile = options.uv_tile.unwrap_or((1, 1, 0));
+        for (u, v) in tile_uvs(points, (width, height), uv_tile, options.flip_v) {
+            writeln!(writer, "vt {} {}", u, v)?;
+        }
+    }
+
+    if options.normals {
+        for normal in compute_vertex_normals(points, triangles, height_data, width) {
+            writeln!(writer, "vn {} {} {}", normal[0], normal[1], normal[2])?;
+        }
+    }
+
+    for (a, b, c) in triangles {
+        let [a, b, c] = [a, b, c].map(|vertex| vertex + 1);
+        match (options.uvs, options.normals) {
+            (true, true) => writeln!(writer, "f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}", a, b, c)?,
+            (true, false) => writeln!(writer, "f {0}/{0} {1}/{1} {2}/{2}", a, b, c)?,
+            (false, true) => writeln!(writer, "f {}//{} {}//{} {}//{}", a, a, b, b, c, c)?,
+            (false, false) => writeln!(writer, "f {} {} {}", a, b, c)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Texture coordinates of points for a tile placed in a shared texture atlas.
+///
+/// Atlas is split into `cols` × `rows` equal cells numbered in row-major order starting
+/// at the top-left cell, so tile `index` occupies column `index % cols` and row `index / cols`.
+/// Within its cell the tile is mapped the same way as a whole texture: `u` grows with `x`
+/// to the right and `v` grows upwards, so the first grid row lies at the top edge of the cell.
+/// Use `(1, 1, 0)` to map a single tile onto the whole texture.
+///
+/// # Arguments
+///
+/// * `points` - Vertices of the mesh.
+/// * `dimensions` - The width and height of the grid the mesh was built from.
+/// * `uv_tile` - Columns and rows of the atlas and index of the tile cell.
+///
+/// # Returns
+///
+/// `(u, v)` coordinates in `0.0..=1.0` for every point.
+///
+/// # Panics
+///
+/// Panics if the atlas has no cells or `index` is not less than `cols * rows`.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::atlas_uvs;
+///
+/// let corners = vec![(0, 0), (4, 0), (0, 4), (4, 4)];
+/// let uvs = atlas_uvs(&corners, (5, 5), (2, 2, 1));
+/// assert_eq!(uvs, vec![(0.5, 1.0), (1.0, 1.0), (0.5, 0.5), (1.0, 0.5)]);
+/// ```
+pub fn atlas_uvs(
+    points: &[Point],
+    dimensions: (usize, usize),
+    uv_tile: (usize, usize, usize),
+) -> Vec<(f64, f64)> {
+    tile_uvs(points, dimensions, uv_tile, true).collect()
+}
+
+/// Texture coordinates of points in the atlas cell, with `v` growing downwards
+/// unless `flip_v` is set.
+fn tile_uvs(
+    points: &[Point],
+    dimensions: (usize, usize),
+    uv_tile: (usize, usize, usize),
+    flip_v: bool,
+) -> impl Iterator<Item = (f64, f64)> + '_ {
+    let (width, height) = dimensions;
+    let (cols, rows, index) = uv_tile;
+    assert!(index < cols * rows, "Tile index is outside of the atlas.");
+    let column = (index % cols) as f64;
+    let row = (index / cols) as f64;
+    let max_x = width.saturating_sub(1).max(1) as f64;
+    let max_y = height.saturating_sub(1).max(1) as f64;
+
+    points.iter().map(move |&(x, y)| {
+        let u = (column + x as f64 / max_x) / cols as f64;
+        let v = (row + y as f64 / max_y) / rows as f64;
+        (u, if flip_v { 1.0 - v } else { v })
+    })
+}
+
+/// Writes mesh in binary STL format, e.g. for 3D printer slicers.
+///
+/// Output starts with 80-byte header filled with zeros and little-endian `u32` triangle count,
+/// followed by 50-byte record for every triangle: unit face normal and three vertex positions
+/// as little-endian `f32` values and zero `u16` attribute. STL has no shared vertices, so every
+/// triangle has its own copies of positions. Vertices are ordered counterclockwise seen from
+/// above, so normals point up. Degenerate (zero-area) triangles get zero normal.
+///
+/// # Arguments
+///
+/// * `writer` - Destination of the STL bytes.
+/// * `points` - Vertices of the mesh.
+/// * `triangles` - Triangles of the mesh, each defined by indices into the `points`.
+/// * `height_data` - Height values of the grid the mesh was built from.
+/// * `width` - The width of the grid.
+/// * `z_offset` - Constant added to every height, e.g. a datum shift. Heights are not scaled
+///   or normalized, so `z` of a vertex is its grid sample plus the offset. Normals don't change.
+///
+/// # Errors
+///
+/// Errors of the writer, or `InvalidInput` if there are more triangles than STL can hold.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::write_stl_binary;
+/// use delatin::{triangulate, Error};
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let (points, triangles) = triangulate(&heights, (2, 2), Error(1.0))?;
+///
+/// let mut stl = Vec::new();
+/// write_stl_binary(&mut stl, &points, &triangles, &heights, 2, 0.0).unwrap();
+///
+/// assert_eq!(stl.len(), 80 + 4 + 2 * 50);
+/// assert_eq!(u32::from_le_bytes(stl[80..84].try_into().unwrap()), 2);
+/// for record in stl[84..].chunks_exact(50) {
+///     let normal_z = f32::from_le_bytes(record[8..12].try_into().unwrap());
+///     assert!(normal_z > 0.0);
+/// }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn write_stl_binary<H: HeightValue, W: Write>(
+    mut writer: W,
+    points: &[Point],
+    triangles: &[Triangle],
+    height_data: &[H],
+    width: usize,
+    z_offset: f64,
+) -> io::Result<()> {
+    let triangle_count = u32::try_from(triangles.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Too many triangles for STL format.",
+        )
+    })?;
+    writer.write_all(&[0; 80])?;
+    writer.write_all(&triangle_count.to_le_bytes())?;
+
+    for &(a, b, c) in triangles {
+        let [point_a, mut point_b, mut point_c] = [a, b, c].map(|vertex| {
+            let point = points[vertex];
+            [
+                point.0 as f64,
+                point.1 as f64,
+                height_data[point.1 * width + point.0].to_f64() + z_offset,
+            ]
+        });
+        let ab = [0, 1, 2].map(|i| point_b[i] - point_a[i]);
+        let ac = [0, 1, 2].map(|i| point_c[i] - point_a[i]);
+        let mut normal = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+        if normal[2] < 0.0 {
+            normal = normal.map(|value| -value);
+            std::mem::swap(&mut point_b, &mut point_c);
+        }
+        let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+        if length > 0.0 {
+            normal = normal.map(|value| value / length);
+        } else {
+            normal = [0.0; 3];
+        }
+
+        for vector in [normal, point_a, point_b, point_c] {
+            for value in vector {
+                writer.write_all(&(value as f32).to_le_bytes())?;
+            }
+        }
+        writer.write_all(&0u16.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Writes mesh in PLY format, with vertex elevation as the `z` property.
+///
+/// Header declares `element vertex N` with `float` properties `x`, `y`, `z` and `element face M`
+/// with `list uchar int vertex_indices`. Vertices are written in the order of `points`,
+/// so the 0-based indices in faces, written as `3 a b c`, refer to them directly.
+/// Binary output uses `binary_little_endian 1.0` format.
+///
+/// # Arguments
+///
+/// * `writer` - Destination of the PLY data.
+/// * `points` - Vertices of the mesh.
+/// * `triangles` - Triangles of the mesh, each defined by indices into the `points`.
+/// * `height_data` - Height values of the grid the mesh was built from.
+/// * `width` - The width of the grid.
+/// * `ascii` - Write ASCII format if `true`, binary otherwise.
+/// * `z_offset` - Constant added to every height before converting it to `float`, e.g. a datum
+///   shift. Heights are not scaled or normalized.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::write_ply;
+///
+/// let heights = vec![100.5, 123.0, 111.5, 121.25];
+/// let points = vec![(0, 0), (1, 0), (1, 1), (0, 1)];
+/// let triangles = vec![(2, 0, 3), (0, 2, 1)];
+///
+/// let mut ply = Vec::new();
+/// write_ply(&mut ply, &points, &triangles, &heights, 2, true, 0.0).unwrap();
+/// let ply = String::from_utf8(ply).unwrap();
+///
+/// assert!(ply.starts_with("ply\nformat ascii 1.0\n"));
+/// assert!(ply.contains("element vertex 4\n"));
+/// assert!(ply.contains("element face 2\n"));
+/// assert!(ply.ends_with("end_header\n0 0 100.5\n1 0 123\n1 1 121.25\n0 1 111.5\n3 2 0 3\n3 0 2 1\n"));
+///
+/// let mut binary = Vec::new();
+/// write_ply(&mut binary, &points, &triangles, &heights, 2, false, 0.0).unwrap();
+/// let header_length = binary.windows(11).position(|window| window == b"end_header\n").unwrap() + 11;
+/// assert_eq!(binary.len() - header_length, 4 * 3 * 4 + 2 * (1 + 3 * 4));
+/// ```
+pub fn write_ply<H: HeightValue, W: Write>(
+    mut writer: W,
+    points: &[Point],
+    triangles: &[Triangle],
+    height_data: &[H],
+    width: usize,
+    ascii: bool,
+    z_offset: f64,
+) -> io::Result<()> {
+    let format = if ascii {
+        "ascii"
+    } else {
+        "binary_little_endian"
+    };
+    write!(
+        writer,
+        "ply\n\
+         format {} 1.0\n\
+         element vertex {}\n\
+         property float x\n\
+         property float y\n\
+         property float z\n\
+         element face {}\n\
+         property list uchar int vertex_indices\n\
+         end_header\n",
+        format,
+        points.len(),
+        triangles.len()
+    )?;
+
+    for point in points {
+        let height = height_data[point.1 * width + point.0].to_f64() + z_offset;
+        let position = [point.0 as f32, point.1 as f32, height as f32];
+        if ascii {
+            writeln!(writer, "{} {} {}", position[0], position[1], position[2])?;
+        } else {
+            for value in position {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+    }
+
+    for &(a, b, c) in triangles {
+        if ascii {
+            writeln!(writer, "3 {} {} {}", a, b, c)?;
+        } else {
+            writer.write_all(&[3])?;
+            for index in [a, b, c] {
+                writer.write_all(&(index as i32).to_le_bytes())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes mesh as an SVG wireframe, e.g. for documentation and quick visual diffs.
+///
+/// Every edge is written once as a `<line>` element, in the order of triangles, even if it
+/// is shared by two triangles. The `viewBox` spans the grid from pixel `(0, 0)` to
+/// `(width - 1, height - 1)`, so coordinates of lines are the grid coordinates of points
+/// and y grows downwards like rows of the grid. Lines keep the same on-screen thickness
+/// regardless of the size the image is drawn at.
+///
+/// # Arguments
+///
+/// * `writer` - Destination of the SVG text.
+/// * `points` - Vertices of the mesh.
+/// * `triangles` - Triangles of the mesh, each defined by indices into the `points`.
+/// * `width` - The width of the grid.
+/// * `height` - The height of the grid.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::write_svg;
+///
+/// let points = vec![(0, 0), (4, 0), (4, 2), (0, 2)];
+/// let triangles = vec![(2, 0, 3), (0, 2, 1)];
+///
+/// let mut svg = Vec::new();
+/// write_svg(&mut svg, &points, &triangles, 5, 3).unwrap();
+/// let svg = String::from_utf8(svg).unwrap();
+///
+/// assert!(svg.contains(r#"viewBox="0 0 4 2""#));
+/// // four sides of the rectangle and the shared diagonal
+/// assert_eq!(svg.matches("<line ").count(), 5);
+/// assert_eq!(svg.matches(r#"<line x1="4" y1="2" x2="0" y2="0"/>"#).count(), 1);
+/// ```
+pub fn write_svg<W: Write>(
+    mut writer: W,
+    points: &[Point],
+    triangles: &[Triangle],
+    width: usize,
+    height: usize,
+) -> io::Result<()> {
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {} {}">"#,
+        width.saturating_sub(1),
+        height.saturating_sub(1)
+    )?;
+    // `vector-effect` is not inherited from the group, so it's set for every line by style
+    writeln!(
+        writer,
+        "<style>line {{ vector-effect: non-scaling-stroke; }}</style>"
+    )?;
+    writeln!(
+        writer,
+        r#"<g stroke="black" stroke-width="1" stroke-linecap="round">"#
+    )?;
+
+    let mut written_edges = HashSet::new();
+    for &(a, b, c) in triangles {
+        for (start, end) in [(a, b), (b, c), (c, a)] {
+            if !written_edges.insert((start.min(end), start.max(end))) {
+                continue;
+            }
+
+            let (start, end) = (points[start], points[end]);
+            writeln!(
+                writer,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}"/>"#,
+                start.0, start.1, end.0, end.1
+            )?;
+        }
+    }
+
+    writeln!(writer, "</g>")?;
+    writeln!(writer, "</svg>")
+}
+
+/// Encodes mesh as a self-contained binary glTF 2.0 (`.glb`), e.g. for web viewers.
+///
+/// The file has one mesh with one triangle primitive, `f32` `POSITION` accessor with accurate
+/// min and max bounds and `u32` indices accessor, both in the embedded binary buffer.
+/// glTF is Y-up, so grid x becomes `X`, height becomes `Y` and grid y becomes `Z`,
+/// with triangles facing up.
+///
+/// Available with the `gltf` feature.
+///
+/// # Arguments
+///
+/// * `points` - Vertices of the mesh.
+/// * `triangles` - Triangles of the mesh, each defined by indices into the `points`.
+/// * `height_data` - Height values of the grid the mesh was built from.
+/// * `width` - The width of the grid.
+/// * `z_offset` - Constant added to every height, which is the `y` coordinate in glTF, before
+///   converting it to `f32`, e.g. a datum shift. Heights are not scaled or normalized.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::write_gltf;
+/// use delatin::{triangulate, Error};
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let (points, triangles) = triangulate(&heights, (2, 2), Error(1.0))?;
+///
+/// let glb = write_gltf(&points, &triangles, &heights, 2, 0.0);
+/// assert_eq!(&glb[..4], b"glTF");
+/// assert_eq!(u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize, glb.len());
+/// let json_length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+/// let json = std::str::from_utf8(&glb[20..20 + json_length]).unwrap();
+/// assert!(json.contains(r#""min":[0,100.1,0],"max":[1,123.4,1]"#));
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[cfg(feature = "gltf")]
+pub fn write_gltf<H: HeightValue>(
+    points: &[Point],
+    triangles: &[Triangle],
+    height_data: &[H],
+    width: usize,
+    z_offset: f64,
+) -> Vec<u8> {
+    const GLB_MAGIC: &[u8] = b"glTF";
+    const JSON_CHUNK: u32 = 0x4E4F_534A;
+    const BIN_CHUNK: u32 = 0x004E_4942;
+
+    let mut binary = Vec::with_capacity(points.len() * 12 + triangles.len() * 12);
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for point in points {
+        let height = height_data[point.1 * width + point.0].to_f64() + z_offset;
+        let position = [point.0 as f32, height as f32, point.1 as f32];
+        for i in 0..3 {
+            min[i] = min[i].min(position[i]);
+            max[i] = max[i].max(position[i]);
+            binary.extend_from_slice(&position[i].to_le_bytes());
+        }
+    }
+    if points.is_empty() {
+        min = [0.0; 3];
+        max = [0.0; 3];
+    }
+    let positions_length = binary.len();
+    for &(a, b, c) in triangles {
+        for index in [a, b, c] {
+            binary.extend_from_slice(&(index as u32).to_le_bytes());
+        }
+    }
+    let indices_length = binary.len() - positions_length;
+
+    let mut json = format!(
+        concat!(
+            r#"{{"asset":{{"version":"2.0","generator":"delatin"}},"scene":0,"#,
+            r#""scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0}}],"#,
+            r#""meshes":[{{"primitives":[{{"attributes":{{"POSITION":0}},"indices":1,"mode":4}}]}}],"#,
+            r#""accessors":["#,
+            r#"{{"bufferView":0,"componentType":5126,"count":{},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}},"#,
+            r#"{{"bufferView":1,"componentType":5125,"count":{},"type":"SCALAR"}}],"#,
+            r#""bufferViews":["#,
+            r#"{{"buffer":0,"byteOffset":0,"byteLength":{},"target":34962}},"#,
+            r#"{{"buffer":0,"byteOffset":{},"byteLength":{},"target":34963}}],"#,
+            r#""buffers":[{{"byteLength":{}}}]}}"#
+        ),
+        points.len(),
+        min[0],
+        min[1],
+        min[2],
+        max[0],
+        max[1],
+        max[2],
+        triangles.len() * 3,
+        positions_length,
+        positions_length,
+        indices_length,
+        binary.len(),
+    );
+
+    // chunks are aligned to 4 bytes, JSON with spaces and binary with zeros
+    while json.len() % 4 != 0 {
+        json.push(' ');
+    }
+    while binary.len() % 4 != 0 {
+        binary.push(0);
+    }
+
+    let total_length = 12 + 8 + json.len() + 8 + binary.len();
+    let mut glb = Vec::with_capacity(total_length);
+    glb.extend_from_slice(GLB_MAGIC);
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(total_length as u32).to_le_bytes());
+    glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&JSON_CHUNK.to_le_bytes());
+    glb.extend_from_slice(json.as_bytes());
+    glb.extend_from_slice(&(binary.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&BIN_CHUNK.to_le_bytes());
+    glb.extend_from_slice(&binary);
+
+    glb
+}
+
+/// Encodes mesh as a quantized-mesh-1.0 terrain tile, e.g. for serving terrain to CesiumJS.
+///
+/// Vertices are quantized to `0..=32767`: `u` from west to east, `v` from south to north and
+/// height from the lowest to the highest vertex. Grid x grows to the east and grid y to the
+/// south, so the mesh is expected to span the whole grid. Vertices are reordered by their first
+/// use in triangles for high-water mark encoding of indices, which are 32-bit if there are more
+/// than 65536 vertices. Edge vertices are taken from boundary half-edges of the mesh and sorted
+/// along their edge. No extensions are written.
+///
+/// Header is computed on the WGS84 ellipsoid. The bounding sphere is centered at the center of
+/// the tile, which isn't the smallest sphere but contains all vertices. The horizon occlusion
+/// point is at the center of the Earth for tiles too large to compute it, e.g. spanning more
+/// than a hemisphere.
+///
+/// Available with the `quantized-mesh` feature.
+///
+/// # Arguments
+///
+/// * `mesh` - Triangulated mesh of the grid.
+/// * `height_data` - Height values of the grid the mesh was built from, in meters.
+/// * `width` - The width of the grid.
+/// * `bounds` - West, south, east and north edges of the grid in degrees.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::write_quantized_mesh;
+/// use delatin::{triangulate_mesh, Error};
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) as f64 / 4.0).sin() * ((i / 33) as f64 / 6.0).cos() * 50.0 + 100.0)
+///     .collect::<Vec<f64>>();
+/// let mesh = triangulate_mesh(&heights, (33, 33), Error(1.0))?;
+///
+/// let tile = write_quantized_mesh(&mesh, &heights, 33, (10.0, 45.0, 10.5, 45.5));
+/// let read_u32 = |offset: usize| u32::from_le_bytes(tile[offset..offset + 4].try_into().unwrap());
+///
+/// let vertex_count = read_u32(88) as usize;
+/// assert_eq!(vertex_count, mesh.points().len());
+/// // u values are zig-zag encoded deltas
+/// let mut u = 0i32;
+/// let mut us = Vec::new();
+/// for i in 0..vertex_count {
+///     let code = u16::from_le_bytes([tile[92 + 2 * i], tile[93 + 2 * i]]) as i32;
+///     u += (code >> 1) ^ -(code & 1);
+///     us.push(u);
+/// }
+/// assert_eq!(us.iter().min(), Some(&0));
+/// assert_eq!(us.iter().max(), Some(&32767));
+/// assert_eq!(read_u32(92 + 6 * vertex_count) as usize, mesh.triangles().len());
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[cfg(feature = "quantized-mesh")]
+pub fn write_quantized_mesh<H: HeightValue>(
+    mesh: &Mesh,
+    height_data: &[H],
+    width: usize,
+    bounds: (f64, f64, f64, f64),
+) -> Vec<u8> {
+    const MAX_QUANTIZED: f64 = 32767.0;
+
+    let height = height_data.len().checked_div(width).unwrap_or(0);
+    let points = mesh.points();
+    let triangles = mesh.triangles_flat();
+    let (west, south, east, north) = bounds;
+
+    // vertices in order of their first use, unused ones last
+    let mut new_indices = vec![None; points.len()];
+    let mut order = Vec::with_capacity(points.len());
+    for vertex in triangles.iter().copied().chain(0..points.len()) {
+        if new_indices[vertex].is_none() {
+            new_indices[vertex] = Some(order.len());
+            order.push(vertex);
+        }
+    }
+    let new_index = |vertex: usize| new_indices[vertex].expect("Every vertex is ordered.");
+
+    let vertex_height = |vertex: usize| {
+        let (x, y) = points[vertex];
+        height_data[y * width + x].to_f64()
+    };
+    let (min_height, max_height) = order
+        .iter()
+        .map(|&vertex| vertex_height(vertex))
+        .fold(None, |range: Option<(f64, f64)>, value| {
+            Some(range.map_or((value, value), |(min, max)| {
+                (min.min(value), max.max(value))
+            }))
+        })
+        .unwrap_or_default();
+    let quantize = |value: f64, min: f64, max: f64| {
+        if max > min {
+            ((value - min) / (max - min) * MAX_QUANTIZED).round() as u16
+        } else {
+            0
+        }
+    };
+    let quantized = order
+        .iter()
+        .map(|&vertex| {
+            let (x, y) = points[vertex];
+            [
+                quantize(x as f64, 0.0, width.saturating_sub(1) as f64),
+                quantize(
+                    (height.saturating_sub(1) - y) as f64,
+                    0.0,
+                    height.saturating_sub(1) as f64,
+                ),
+                quantize(vertex_height(vertex), min_height, max_height),
+            ]
+        })
+        .collect::<Vec<[u16; 3]>>();
+
+    // positions on the ellipsoid for the header
+    let to_geodetic = |vertex: usize| {
+        let (x, y) = points[vertex];
+        let longitude = west + (east - west) * x as f64 / width.saturating_sub(1).max(1) as f64;
+        let latitude = north - (north - south) * y as f64 / height.saturating_sub(1).max(1) as f64;
+        (longitude, latitude, vertex_height(vertex))
+    };
+    let center = geodetic_to_ecef(
+        (west + east) / 2.0,
+        (south + north) / 2.0,
+        (min_height + max_height) / 2.0,
+    );
+    let positions = order
+        .iter()
+        .map(|&vertex| {
+            let (longitude, latitude, height) = to_geodetic(vertex);
+            geodetic_to_ecef(longitude, latitude, height)
+        })
+        .collect::<Vec<[f64; 3]>>();
+    let radius = positions
+        .iter()
+        .map(|position| distance(*position, center))
+        .fold(0.0, f64::max);
+    let horizon_occlusion_point = horizon_occlusion_point(center, &positions);
+
+    let mut tile = Vec::with_capacity(88 + 4 + 6 * order.len() + 4 + 12 * triangles.len() / 3);
+    for value in center {
+        tile.extend_from_slice(&value.to_le_bytes());
+    }
+    tile.extend_from_slice(&(min_height as f32).to_le_bytes());
+    tile.extend_from_slice(&(max_height as f32).to_le_bytes());
+    for value in center {
+        tile.extend_from_slice(&value.to_le_bytes());
+    }
+    tile.extend_from_slice(&radius.to_le_bytes());
+    for value in horizon_occlusion_point {
+        tile.extend_from_slice(&value.to_le_bytes());
+    }
+
+    tile.extend_from_slice(&(order.len() as u32).to_le_bytes());
+    for component in 0..3 {
+        let mut previous = 0i32;
+        for vertex in &quantized {
+            let value = vertex[component] as i32;
+            let delta = value - previous;
+            tile.extend_from_slice(&(((delta << 1) ^ (delta >> 31)) as u16).to_le_bytes());
+            previous = value;
+        }
+    }
+
+    let is_wide = order.len() > 65536;
+    let push_index = |tile: &mut Vec<u8>, index: usize| {
+        if is_wide {
+            tile.extend_from_slice(&(index as u32).to_le_bytes());
+        } else {
+            tile.extend_from_slice(&(index as u16).to_le_bytes());
+        }
+    };
+    // 32-bit indices are aligned to 4 bytes, vertex data always ends aligned to 2
+    if is_wide && tile.len() % 4 != 0 {
+        tile.extend_from_slice(&[0, 0]);
+    }
+    tile.extend_from_slice(&((triangles.len() / 3) as u32).to_le_bytes());
+    let mut highest = 0;
+    for &vertex in triangles {
+        let index = new_index(vertex);
+        push_index(&mut tile, highest - index);
+        if index == highest {
+            highest += 1;
+        }
+    }
+
+    // boundary vertices on every edge of the grid, sorted along the edge
+    let boundary_vertices = mesh
+        .boundary_loops()
+        .into_iter()
+        .flatten()
+        .map(|edge| triangles[edge])
+        .collect::<HashSet<usize>>();
+    // west, south, east and north in the order of the format
+    let on_edge = |point: Point, edge: usize| match edge {
+        0 => point.0 == 0,
+        1 => point.1 + 1 == height,
+        2 => point.0 + 1 == width,
+        _ => point.1 == 0,
+    };
+    for edge in 0..4 {
+        let mut edge_vertices = boundary_vertices
+            .iter()
+            .copied()
+            .filter(|&vertex| on_edge(points[vertex], edge))
+            .collect::<Vec<usize>>();
+        // west and east from south to north, south and north from west to east
+        edge_vertices.sort_unstable_by_key(|&vertex| match edge {
+            0 | 2 => (usize::MAX - points[vertex].1, vertex),
+            _ => (points[vertex].0, vertex),
+        });
+        tile.extend_from_slice(&(edge_vertices.len() as u32).to_le_bytes());
+        for vertex in edge_vertices {
+            push_index(&mut tile, new_index(vertex));
+        }
+    }
+
+    tile
+}
+
+/// Writes mesh as two NumPy `.npy` arrays.
+///
+/// Vertices are written as `(N, 3)` array of little-endian `f64` (`<f8`) with `x, y, z` rows,
+/// faces as `(M, 3)` array of little-endian `i64` (`<i8`) with 0-based indices into vertices.
+/// Both use `.npy` format version 1.0: magic string `\x93NUMPY`, version bytes `1 0`,
+/// little-endian `u16` header length, then a Python dict literal header padded with spaces
+/// and a newline so that data starts at a multiple of 64 bytes, followed by C-ordered data.
+///
+/// # Arguments
+///
+/// * `vertices_writer` - Destination of the vertices array.
+/// * `faces_writer` - Destination of the faces array.
+/// * `points` - Vertices of the mesh.
+/// * `triangles` - Triangles of the mesh, each defined by indices into the `points`.
+/// * `height_data` - Height values of the grid the mesh was built from.
+/// * `width` - The width of the grid.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::write_npy;
+/// use delatin::{triangulate, Error};
+///
+/// // reads header of .npy array and returns its shape and data
+/// fn parse_npy(bytes: &[u8]) -> (String, &[u8]) {
+///     assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+///     let header_length = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+///     let header = std::str::from_utf8(&bytes[10..10 + header_length]).unwrap();
+///     let shape_start = header.find("'shape': (").unwrap() + 10;
+///     let shape_end = shape_start + header[shape_start..].find(')').unwrap();
+///
+///     (header[shape_start..shape_end].to_string(), &bytes[10 + header_length..])
+/// }
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let (points, triangles) = triangulate(&heights, (2, 2), Error(1.0))?;
+///
+/// let mut vertices = Vec::new();
+/// let mut faces = Vec::new();
+/// write_npy(&mut vertices, &mut faces, &points, &triangles, &heights, 2).unwrap();
+///
+/// let (shape, data) = parse_npy(&vertices);
+/// assert_eq!(shape, "4, 3");
+/// assert_eq!(data.len(), 4 * 3 * 8);
+/// let (shape, data) = parse_npy(&faces);
+/// assert_eq!(shape, "2, 3");
+/// assert_eq!(data.len(), 2 * 3 * 8);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn write_npy<H: HeightValue, V: Write, F: Write>(
+    mut vertices_writer: V,
+    mut faces_writer: F,
+    points: &[Point],
+    triangles: &[Triangle],
+    height_data: &[H],
+    width: usize,
+) -> io::Result<()> {
+    write_npy_header(&mut vertices_writer, "<f8", points.len())?;
+    for point in points {
+        let height = height_data[point.1 * width + point.0].to_f64();
+        for value in [point.0 as f64, point.1 as f64, height] {
+            vertices_writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+
+    write_npy_header(&mut faces_writer, "<i8", triangles.len())?;
+    for (a, b, c) in triangles {
+        for index in [a, b, c] {
+            faces_writer.write_all(&(*index as i64).to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes `.npy` version 1.0 header of C-ordered array with `rows` rows and 3 columns.
+fn write_npy_header<W: Write>(writer: &mut W, descr: &str, rows: usize) -> io::Result<()> {
+    const MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+    const ALIGNMENT: usize = 64;
+
+    let mut header = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({}, 3), }}",
+        descr, rows
+    );
+    // magic, header length and newline at the end
+    let unpadded_length = MAGIC.len() + 2 + header.len() + 1;
+    let padding = (ALIGNMENT - unpadded_length % ALIGNMENT) % ALIGNMENT;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())
+}
+
+/// Writer which only counts bytes written into it.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Radii of the WGS84 ellipsoid in meters.
+#[cfg(feature = "quantized-mesh")]
+const WGS84_RADII: [f64; 3] = [6_378_137.0, 6_378_137.0, 6_356_752.314_245_179];
+/// Squared first eccentricity of the WGS84 ellipsoid.
+#[cfg(feature = "quantized-mesh")]
+const WGS84_ECCENTRICITY_SQUARED: f64 = 0.006_694_379_990_141_317;
+
+/// Earth-centered fixed coordinates of the point on the WGS84 ellipsoid.
+#[cfg(feature = "quantized-mesh")]
+fn geodetic_to_ecef(longitude: f64, latitude: f64, height: f64) -> [f64; 3] {
+    let (sin_longitude, cos_longitude) = longitude.to_radians().sin_cos();
+    let (sin_latitude, cos_latitude) = latitude.to_radians().sin_cos();
+    let prime_vertical_radius =
+        WGS84_RADII[0] / (1.0 - WGS84_ECCENTRICITY_SQUARED * sin_latitude * sin_latitude).sqrt();
+
+    [
+        (prime_vertical_radius + height) * cos_latitude * cos_longitude,
+        (prime_vertical_radius + height) * cos_latitude * sin_longitude,
+        (prime_vertical_radius * (1.0 - WGS84_ECCENTRICITY_SQUARED) + height) * sin_latitude,
+    ]
+}
+
+#[cfg(feature = "quantized-mesh")]
+fn distance(a: [f64; 3], b: [f64; 3]) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// Point in ellipsoid-scaled coordinates which is below the horizon only when all positions
+/// are, along the direction to the center, the same way as Cesium computes it.
+#[cfg(feature = "quantized-mesh")]
+fn horizon_occlusion_point(center: [f64; 3], positions: &[[f64; 3]]) -> [f64; 3] {
+    let scale = |position: [f64; 3]| [0, 1, 2].map(|i| position[i] / WGS84_RADII[i]);
+    let dot = |a: [f64; 3], b: [f64; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+
+    let scaled_center = scale(center);
+    let center_length = dot(scaled_center, scaled_center).sqrt();
+    if center_length == 0.0 {
+        return [0.0; 3];
+    }
+    let direction = scaled_center.map(|value| value / center_length);
+
+    let mut magnitude: f64 = 0.0;
+    for &position in positions {
+        let scaled = scale(position);
+        let length = dot(scaled, scaled).sqrt().max(1.0);
+        let cos_alpha = dot(scaled, direction) / dot(scaled, scaled).sqrt();
+        let sin_alpha = (1.0 - cos_alpha * cos_alpha).max(0.0).sqrt();
+        let cos_beta = 1.0 / length;
+        let sin_beta = (length * length - 1.0).sqrt() * cos_beta;
+        let denominator = cos_alpha * cos_beta - sin_alpha * sin_beta;
+        if denominator <= 0.0 {
+            return [0.0; 3];
+        }
+        magnitude = magnitude.max(1.0 / denominator);
+    }
+
+    direction.map(|value| value * magnitude)
+}
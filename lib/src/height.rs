@@ -0,0 +1,68 @@
+use std::fmt::Debug;
+
+/// Numeric type of height values the grid can be triangulated from.
+///
+/// Heights are read from the grid as they are stored and converted to `f64` only for
+/// computing errors, so grids stored as `f32` take half the memory of `f64` ones.
+/// Heights are shared between threads with the `rayon` feature, so they have to be `Sync`
+/// only when it is enabled.
+///
+/// Raw `u16` DEM tiles can be triangulated directly as well. Every `u16` value and difference
+/// of two of them is exact in `f64`, so errors are computed without loss of precision and are
+/// in the units of the stored values, e.g. before applying the scale of the tile.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate, Error, TriangulationOptions};
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) * 1000 + (i / 33) * (i / 33) * 60) as u16)
+///     .collect::<Vec<u16>>();
+/// let (points, triangles) = TriangulationOptions::new()
+///     .max_error(Error(10.0))
+///     .run(&heights, 33, 33)?;
+///
+/// let heights = heights.iter().map(|&height| height as f64).collect::<Vec<f64>>();
+/// assert_eq!((points, triangles), triangulate(&heights, (33, 33), Error(10.0))?);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub trait HeightValue: Copy + Debug + PartialOrd + MaybeSync {
+    /// Converts height to `f64` used for error computations.
+    fn to_f64(self) -> f64;
+}
+
+/// `Sync` with the `rayon` feature and implemented for every type without it.
+#[cfg(feature = "rayon")]
+pub trait MaybeSync: Sync {}
+
+#[cfg(feature = "rayon")]
+impl<T: Sync> MaybeSync for T {}
+
+/// `Sync` with the `rayon` feature and implemented for every type without it.
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "rayon"))]
+impl<T> MaybeSync for T {}
+
+impl HeightValue for f64 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl HeightValue for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl HeightValue for u16 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
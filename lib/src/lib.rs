@@ -13,27 +13,67 @@ let height = 2;
 let max_error = Error(1.0);
 // points `Vec<(usize, usize)>`: A vector containing all the vertices of the triangulated mesh. Each point corresponds to heights vector index.
 // triangles `Vec<(usize, usize, usize)>`: A vector containing all the triangles of the mesh, each defined by indices into the `points`.
-let (points, triangles) = triangulate(&heights, width, height, max_error)?;
+let (points, triangles) = triangulate(&heights, (width, height), max_error)?;
+# Ok::<(), delatin::TriangulationError>(())
 ```
 */
 
 use std::fmt;
+use std::io::{self, Read};
 
+pub use cancel::CancellationToken;
 pub use error::TriangulationError;
-use triangulation::Triangulation;
+use export::ExportFormat;
+pub use height::HeightValue;
+pub use mesh::Mesh;
+pub use metric::{AbsoluteError, ErrorMetric};
+pub use options::TriangulationOptions;
+use progress::JsonlReporter;
+pub use refiner::Refiner;
+pub use sink::MeshSink;
+pub use stats::TriangulationStats;
+pub use threshold::{ErrorThreshold, RelativeError, StopCriterion};
+use triangulation::{Triangulation, MAX_PIXELS};
+use utils::is_nodata_value;
 
+mod cancel;
 mod error;
+pub mod export;
+mod height;
+mod mesh;
+mod metric;
+mod options;
+pub mod predicates;
 mod priority_queue;
+pub mod progress;
+mod refiner;
+mod sink;
+mod stats;
+mod threshold;
 mod triangulation;
 mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // TODO: consider NewTypes
 type Point = (usize, usize);
 type Triangle = (usize, usize, usize);
 type Height = f64;
+type PointWithHeight = (usize, usize, Height);
+
+/// Number of triangulations done by [`estimate_error_for_size`].
+const SIZE_ESTIMATION_PROBES: usize = 10;
+
+/// Maximum number of triangulations done by [`triangulate_to_budget`].
+const BUDGET_SEARCH_PROBES: usize = 32;
+
+/// Number of rows [`triangulate_from_reader`] reads before triangulating them as a band,
+/// besides the row shared with the previous band.
+const READER_BAND_ROWS: usize = 256;
 
 /// Error for the triangulation process.
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Error(pub f64);
 
 impl fmt::Display for Error {
@@ -42,6 +82,29 @@ impl fmt::Display for Error {
     }
 }
 
+/// Height data of a tile with its dimensions, see [`triangulate_tiles`].
+///
+/// Available with the `rayon` feature.
+#[cfg(feature = "rayon")]
+#[derive(Debug, Clone, Copy)]
+pub struct TileRef<'a> {
+    /// Height values of the tile.
+    pub height_data: &'a [f64],
+    /// Tuple width and height of the tile.
+    pub dimensions: (usize, usize),
+}
+
+#[cfg(feature = "rayon")]
+impl<'a> TileRef<'a> {
+    /// Creates a tile of the height data with the dimensions.
+    pub fn new(height_data: &'a [f64], dimensions: (usize, usize)) -> Self {
+        Self {
+            height_data,
+            dimensions,
+        }
+    }
+}
+
 /// Runs the triangulation process until the maximum error is below the specified threshold.
 ///
 /// # Arguments
@@ -56,24 +119,1227 @@ impl fmt::Display for Error {
 /// - points `Vec<(usize, usize)>`: A vector containing all the vertices of the triangulated mesh.
 /// - triangles `Vec<(usize, usize, usize)>`: A vector containing all the triangles of the mesh, each defined by indices into the `points`.
 ///
+/// All triangles wind counterclockwise when drawn with y growing downwards like rows of the
+/// grid, so [`signed_area`](predicates::signed_area) of each is positive. The same holds for
+/// all other triangulation functions, also with [`TriangulationOptions::flip_y`] which keeps
+/// the area positive. See [`Mesh::ensure_ccw`] for meshes from other sources.
+///
 /// # Errors
 ///
 /// - `InvalidDataLengthError` - If the length of the height data does not match the width and height of the grid.
+/// - `GridTooSmall` - If the width or height of the grid is less than 2.
+/// - `GridTooLarge` - If the grid has too many pixels for the `u32-indices` feature.
+/// - `NonFiniteHeight` - If any height is `NaN` or infinite, with the index of the first one.
 /// - `MaxErrorRetrievalError` - If the maximum error is not found in the priority queue.
 /// - `EmptyQueueError` - If the priority queue is empty during triangulation.
 ///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate, Error, TriangulationError};
+///
+/// // high-frequency checkerboard needs a vertex at nearly every pixel
+/// let heights = (0..64 * 64)
+///     .map(|i| ((i % 64 + i / 64) % 2) as f64 * 100.0)
+///     .collect::<Vec<f64>>();
+/// let (points, triangles) = triangulate(&heights, (64, 64), Error(0.0))?;
+/// assert!(triangles.len() > 64 * 64 / 4);
+/// assert!(points.len() <= 64 * 64);
+///
+/// // tiny grids work as well
+/// let (_, triangles) = triangulate(&[0.0, 5.0, 0.0, 1.0, 2.0, 3.0], (3, 2), Error(0.0))?;
+/// assert!(!triangles.is_empty());
+///
+/// // but a single row has no area to triangulate
+/// let result = triangulate(&[0.0, 5.0, 0.0], (3, 1), Error(0.0));
+/// assert!(matches!(result, Err(TriangulationError::GridTooSmall { width: 3, height: 1 })));
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
 pub fn triangulate(
     height_data: &[f64],
     dimesions: (usize, usize),
     max_error: Error,
 ) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    let (width, height) = dimesions;
+    TriangulationOptions::new()
+        .max_error(max_error)
+        .run(height_data, width, height)
+}
+
+/// Runs the triangulation process until the maximum error is below the specified threshold.
+///
+/// Same as [`triangulate`], but every point carries its height, so the grid is not needed
+/// to recover it. Height is the grid sample at the exact vertex coordinate, not interpolated.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Returns
+///
+/// Tuple containing:
+/// - points `Vec<(usize, usize, f64)>`: A vector containing all the vertices of the triangulated mesh with their heights.
+/// - triangles `Vec<(usize, usize, usize)>`: A vector containing all the triangles of the mesh, each defined by indices into the `points`.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_with_heights, Error};
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let (points, _) = triangulate_with_heights(&heights, (2, 2), Error(1.0))?;
+///
+/// for (x, y, z) in points {
+///     assert_eq!(z, heights[y * 2 + x]);
+/// }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_with_heights(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    max_error: Error,
+) -> Result<(Vec<PointWithHeight>, Vec<Triangle>), TriangulationError> {
+    let delatin = TriangulationOptions::new()
+        .max_error(max_error)
+        .triangulate(height_data, dimesions, AbsoluteError)?;
+
+    Ok((
+        delatin.get_vertex_points_with_heights(),
+        delatin.get_triangle_indices(),
+    ))
+}
+
+/// Runs the triangulation process until the maximum error is below the specified threshold.
+///
+/// Same as [`triangulate`], but returns a [`Mesh`] which keeps connectivity of triangles.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+pub fn triangulate_mesh(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    max_error: Error,
+) -> Result<Mesh, TriangulationError> {
+    let (width, height) = dimesions;
+    TriangulationOptions::new()
+        .max_error(max_error)
+        .run_mesh(height_data, width, height)
+}
+
+/// Runs the triangulation process until the maximum error is below the specified threshold.
+///
+/// Same as [`triangulate`], but passes points and triangles to the sink instead of returning
+/// them, see [`MeshSink`].
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+/// * `sink` - Receiver of the points and triangles.
+///
+/// # Errors
+///
+/// Same as [`triangulate`]. Nothing is passed to the sink on error.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate, triangulate_streaming, Error};
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+///
+/// let mut collected = (Vec::new(), Vec::new());
+/// triangulate_streaming(&heights, (2, 2), Error(1.0), &mut collected)?;
+/// assert_eq!(collected, triangulate(&heights, (2, 2), Error(1.0))?);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_streaming<S: MeshSink + ?Sized>(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    max_error: Error,
+    sink: &mut S,
+) -> Result<(), TriangulationError> {
+    let (width, height) = dimesions;
+    TriangulationOptions::new()
+        .max_error(max_error)
+        .run_streaming(height_data, width, height, sink)
+}
+
+/// Runs the triangulation process until the maximum error is below the specified threshold.
+///
+/// Same as [`triangulate`], but measures errors of pixels with the metric instead of the
+/// absolute difference of heights, see [`ErrorMetric`]. `max_error` is in units of the metric.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+/// * `metric` - Error of a pixel between its predicted and actual height.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+pub fn triangulate_with_metric<M: ErrorMetric>(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    max_error: Error,
+    metric: M,
+) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    let (width, height) = dimesions;
+    TriangulationOptions::new()
+        .max_error(max_error)
+        .run_with_metric(height_data, width, height, metric)
+}
+
+/// Runs the triangulation process until the maximum error is below the specified threshold.
+///
+/// Same as [`triangulate_mesh`], but also returns the error of every pixel of the final mesh,
+/// see [`Mesh::error_map`]. Computing it rasterizes the whole grid once more.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Returns
+///
+/// A tuple of the mesh and `width * height` errors in the order of `height_data`.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_with_error_map, Error};
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) as f64 / 4.0).sin() * ((i / 33) as f64 / 6.0).cos() * 50.0)
+///     .collect::<Vec<f64>>();
+///
+/// let (mesh, errors) = triangulate_with_error_map(&heights, (33, 33), Error(1.0))?;
+/// assert_eq!(errors.len(), heights.len());
+/// let max_error = errors.iter().copied().fold(0.0, f64::max);
+/// assert!((max_error - mesh.max_error().0).abs() < 1e-9);
+/// // vertices are exact
+/// for &(x, y) in mesh.points() {
+///     assert_eq!(errors[y * 33 + x], 0.0);
+/// }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_with_error_map(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    max_error: Error,
+) -> Result<(Mesh, Vec<f64>), TriangulationError> {
+    let (width, height) = dimesions;
+    let mesh = triangulate_mesh(height_data, dimesions, max_error)?;
+    let errors = mesh.error_map(height_data, width, height);
+
+    Ok((mesh, errors))
+}
+
+/// Runs the triangulation process until the maximum error is below the specified threshold.
+///
+/// Same as [`triangulate_mesh`], but also returns how many refinement steps were done
+/// and the final maximum error, e.g. for tuning `max_error` empirically.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_with_stats, Error};
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+///     .collect::<Vec<f64>>();
+///
+/// let (mesh, stats) = triangulate_with_stats(&heights, (33, 33), Error(1.0))?;
+/// assert!(stats.final_max_error <= Error(1.0));
+/// assert_eq!(stats.final_max_error, mesh.max_error());
+/// // every step inserts one vertex into the initial four corners
+/// assert_eq!(stats.iterations, mesh.points().len() - 4);
+/// assert_eq!(stats.point_count, mesh.points().len());
+/// assert_eq!(stats.triangle_count, mesh.triangles().len());
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_with_stats(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    max_error: Error,
+) -> Result<(Mesh, TriangulationStats), TriangulationError> {
+    let (width, height) = dimesions;
+    TriangulationOptions::new()
+        .max_error(max_error)
+        .run_with_stats(height_data, width, height)
+}
+
+/// Runs the triangulation process with void pixels and returns a mesh for every separate
+/// valid region, e.g. islands surrounded by `nodata`.
+///
+/// Triangulation starts from the four corners of the grid like [`TriangulationOptions::nodata`],
+/// but valid pixels are refined until triangles of valid vertices cover them. Then triangles
+/// with void vertices are dropped with [`Mesh::remove_nodata`] and the rest is split with
+/// [`Mesh::components`]. Regions joined by a triangle of valid vertices, e.g. separated by a
+/// void one pixel wide, stay in one mesh.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+/// * `nodata` - Sentinel height of void pixels, e.g. `-9999.0` or `NaN`.
+///
+/// # Returns
+///
+/// A mesh for every valid region with its own points, empty if the grid is all void.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_regions, Error};
+///
+/// // two flat islands in the void
+/// let heights = (0..17 * 17)
+///     .map(|i| match (i % 17, i / 17) {
+///         (2..=6, 2..=6) => 10.0,
+///         (10..=14, 9..=14) => 20.0,
+///         _ => f64::NAN,
+///     })
+///     .collect::<Vec<f64>>();
+///
+/// let regions = triangulate_regions(&heights, (17, 17), Error(0.5), f64::NAN)?;
+/// assert_eq!(regions.len(), 2);
+/// for region in &regions {
+///     let (min, max) = region.bounds();
+///     assert!(min == (2, 2) && max == (6, 6) || min == (10, 9) && max == (14, 14));
+/// }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_regions(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    max_error: Error,
+    nodata: f64,
+) -> Result<Vec<Mesh>, TriangulationError> {
+    let (width, height) = dimesions;
+    let mesh = TriangulationOptions::new()
+        .max_error(max_error)
+        .nodata(nodata)
+        .cover_valid_pixels(true)
+        .run_mesh(height_data, width, height)?;
+
+    Ok(mesh.remove_nodata(height_data, width, nodata).components())
+}
+
+/// Runs the triangulation process writing its progress as JSON lines.
+///
+/// See [`JsonlReporter`] for the format and the emission interval.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+/// * `reporter` - Reporter receiving every refinement step.
+///
+/// # Errors
+///
+/// Same as [`triangulate`]. Writing errors are kept in the reporter.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::progress::JsonlReporter;
+/// use delatin::{triangulate_with_progress_report, Error};
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+///     .collect::<Vec<f64>>();
+///
+/// let mut reporter = JsonlReporter::new(Vec::new(), 10);
+/// triangulate_with_progress_report(&heights, (33, 33), Error(1.0), &mut reporter)?;
+/// let output = String::from_utf8(reporter.finish().unwrap()).unwrap();
+///
+/// let mut previous_vertices = 0;
+/// for line in output.lines() {
+///     assert!(line.starts_with('{') && line.ends_with('}'));
+///     let vertices = line
+///         .split(',')
+///         .find_map(|field| field.strip_prefix(r#""vertices":"#))
+///         .unwrap()
+///         .parse::<usize>()
+///         .unwrap();
+///     assert!(vertices > previous_vertices);
+///     previous_vertices = vertices;
+/// }
+/// assert!(previous_vertices > 0);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_with_progress_report<W: io::Write>(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    max_error: Error,
+    reporter: &mut JsonlReporter<W>,
+) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    let mut delatin = create_triangulation(height_data, dimesions, AbsoluteError)?;
+    check_finite_heights(height_data, None)?;
+    delatin.initialize();
+    delatin.refine_until_with_progress(max_error, |max_error, vertex_count, triangle_count| {
+        reporter.report(max_error, vertex_count, triangle_count)
+    })?;
+
+    Ok((delatin.get_vertext_points(), delatin.get_triangle_indices()))
+}
+
+/// Runs the triangulation process on heights given row by row, e.g. one vector per scanline.
+///
+/// Height of the grid is the number of rows and width is the length of the first row.
+/// Rows are copied into one row-major vector.
+///
+/// # Arguments
+///
+/// * `rows` - Rows of height values from the top of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Errors
+///
+/// - `InvalidRowLength` - If a row is not as long as the first one, with the index of the first such row.
+///
+/// Otherwise same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate, triangulate_rows, Error, TriangulationError};
+///
+/// let rows = vec![vec![100.1, 123.4, 111.5], vec![121.4, 98.7, 105.2]];
+/// assert_eq!(
+///     triangulate_rows(&rows, Error(1.0))?,
+///     triangulate(&[100.1, 123.4, 111.5, 121.4, 98.7, 105.2], (3, 2), Error(1.0))?
+/// );
+///
+/// let ragged = vec![vec![100.1, 123.4, 111.5], vec![121.4, 98.7]];
+/// assert!(matches!(
+///     triangulate_rows(&ragged, Error(1.0)),
+///     Err(TriangulationError::InvalidRowLength { row: 1 })
+/// ));
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_rows(
+    rows: &[Vec<f64>],
+    max_error: Error,
+) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    let width = rows.first().map_or(0, Vec::len);
+    if let Some(row) = rows.iter().position(|row| row.len() != width) {
+        return Err(TriangulationError::InvalidRowLength { row });
+    }
+    let height_data = rows.concat();
+
+    triangulate(&height_data, (width, rows.len()), max_error)
+}
+
+/// Runs the triangulation process on a rectangular region of a larger grid, without copying
+/// its heights.
+///
+/// Heights are looked up in rows of the full grid, so only the region has to be finite.
+/// Not to be confused with [`triangulate_regions`], which splits the mesh around voids.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the full grid.
+/// * `dimesions` - Tuple width and height of the full grid.
+/// * `region` - Min x, min y, max x, max y of the region, max values are exclusive.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+/// * `absolute` - Whether output points are in coordinates of the full grid instead of
+///   relative to the top left corner of the region.
+///
+/// # Errors
+///
+/// - `InvalidRegionError` - If the region is empty or doesn't fit the grid.
+/// - `GridTooSmall` - If the region is narrower or lower than 2 pixels, with its size.
+/// - `NonFiniteHeight` - If any height inside the region is `NaN` or infinite, with the index of the first one in the full grid.
+///
+/// Otherwise same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate, triangulate_region, Error};
+///
+/// let heights = (0..16 * 12).map(|i| ((i * 7) % 11) as f64).collect::<Vec<f64>>();
+/// let region = (3, 2, 11, 9);
+/// let copied = heights
+///     .chunks_exact(16)
+///     .skip(2)
+///     .take(7)
+///     .flat_map(|row| &row[3..11])
+///     .copied()
+///     .collect::<Vec<f64>>();
+///
+/// let (points, triangles) = triangulate_region(&heights, (16, 12), region, Error(1.0), false)?;
+/// assert_eq!((points.clone(), triangles), triangulate(&copied, (8, 7), Error(1.0))?);
+///
+/// let (absolute_points, _) = triangulate_region(&heights, (16, 12), region, Error(1.0), true)?;
+/// for (point, absolute) in points.iter().zip(&absolute_points) {
+///     assert_eq!(*absolute, (point.0 + 3, point.1 + 2));
+/// }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_region(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    region: (usize, usize, usize, usize),
+    max_error: Error,
+    absolute: bool,
+) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    let (width, height) = dimesions;
+    (height_data.len() == width * height)
+        .then_some(())
+        .ok_or(TriangulationError::InvalidDataLengthError)?;
+    let (min_x, min_y, max_x, max_y) = region;
+    (min_x < max_x && min_y < max_y && max_x <= width && max_y <= height)
+        .then_some(())
+        .ok_or(TriangulationError::InvalidRegionError)?;
+    let (region_width, region_height) = (max_x - min_x, max_y - min_y);
+    check_grid_size(region_width, region_height)?;
+    let non_finite = (min_y..max_y)
+        .flat_map(|y| (min_x..max_x).map(move |x| y * width + x))
+        .find(|&index| !height_data[index].is_finite());
+    if let Some(index) = non_finite {
+        return Err(TriangulationError::NonFiniteHeight { index });
+    }
+
+    let region_data = &height_data[min_y * width + min_x..];
+    let mut delatin = Triangulation::new(region_data, region_width, region_height);
+    delatin.set_stride(width);
+    if absolute {
+        delatin.set_output_offset((min_x, min_y));
+    }
+    delatin.run(max_error)?;
+
+    Ok((delatin.get_vertext_points(), delatin.get_triangle_indices()))
+}
+
+/// Runs the triangulation process on a 2D array, taking width and height from its shape.
+///
+/// Axis 0 of the array is y (rows) and axis 1 is x (columns), so point `(x, y)` corresponds
+/// to `array[[y, x]]`. Arrays not in standard row-major layout, e.g. Fortran-ordered or
+/// transposed views, are copied into row-major layout first.
+///
+/// Available with the `ndarray` feature.
+///
+/// # Arguments
+///
+/// * `array` - Height values of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate, triangulate_array, Error};
+/// use ndarray::{Array2, ShapeBuilder};
+///
+/// let heights = (0..9 * 5).map(|i| ((i * 7) % 11) as f64).collect::<Vec<f64>>();
+/// // 5 rows of 9 columns
+/// let array = Array2::from_shape_vec((5, 9), heights.clone()).unwrap();
+/// let fortran = Array2::from_shape_fn((5, 9).f(), |(y, x)| array[[y, x]]);
+///
+/// let expected = triangulate(&heights, (9, 5), Error(1.0))?;
+/// assert_eq!(triangulate_array(array.view(), Error(1.0))?, expected);
+/// assert_eq!(triangulate_array(fortran.view(), Error(1.0))?, expected);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[cfg(feature = "ndarray")]
+pub fn triangulate_array<H: HeightValue>(
+    array: ndarray::ArrayView2<'_, H>,
+    max_error: Error,
+) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    let (height, width) = array.dim();
+    let array = array.as_standard_layout();
+    let height_data = array
+        .as_slice()
+        .expect("Array in standard layout is contiguous.");
+
+    TriangulationOptions::new()
+        .max_error(max_error)
+        .run(height_data, width, height)
+}
+
+/// Runs the triangulation process on a grayscale heightmap image.
+///
+/// Luma values are multiplied by `z_scale` to get heights. Points are image pixel coordinates
+/// with the origin at the top-left corner, so the mesh aligns with the source texture.
+///
+/// Available with the `image` feature.
+///
+/// # Arguments
+///
+/// * `image` - Heightmap image, 8-bit or 16-bit grayscale.
+/// * `max_error` - The maximum allowable error of scaled heights.
+/// * `z_scale` - Multiplier converting luma values to heights.
+///
+/// # Errors
+///
+/// - `UnsupportedImageError` - If the image is not `Luma8` or `Luma16`, e.g. RGB.
+/// - Same as [`triangulate`] otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_image, Error, TriangulationError};
+/// use image::{DynamicImage, ImageBuffer, Luma, RgbImage};
+///
+/// let heightmap = ImageBuffer::from_fn(9, 5, |x, y| Luma([(x * 1000 + y * 50) as u16]));
+/// let image = DynamicImage::ImageLuma16(heightmap);
+/// let (points, _) = triangulate_image(&image, Error(0.5), 0.1)?;
+/// assert!(points.contains(&(8, 4)));
+///
+/// let rgb = DynamicImage::ImageRgb8(RgbImage::new(9, 5));
+/// assert!(matches!(
+///     triangulate_image(&rgb, Error(0.5), 0.1),
+///     Err(TriangulationError::UnsupportedImageError)
+/// ));
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[cfg(feature = "image")]
+pub fn triangulate_image(
+    image: &image::DynamicImage,
+    max_error: Error,
+    z_scale: f64,
+) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    let height_data = match image {
+        image::DynamicImage::ImageLuma8(buffer) => buffer
+            .as_raw()
+            .iter()
+            .map(|&luma| luma as f64 * z_scale)
+            .collect::<Vec<f64>>(),
+        image::DynamicImage::ImageLuma16(buffer) => buffer
+            .as_raw()
+            .iter()
+            .map(|&luma| luma as f64 * z_scale)
+            .collect::<Vec<f64>>(),
+        _ => return Err(TriangulationError::UnsupportedImageError),
+    };
+
+    triangulate(
+        &height_data,
+        (image.width() as usize, image.height() as usize),
+        max_error,
+    )
+}
+
+/// Reads the grid row by row from a reader and triangulates it in bands as rows arrive.
+///
+/// Each row is `width` heights encoded as little-endian `f64`, the height of the grid
+/// is the number of rows read. Once 256 rows past the previous band are read, they are
+/// triangulated together with the last row of the previous band and all but the last row
+/// are dropped, so only one band of heights is kept in memory. Vertices of the previous
+/// band on the shared row are seed points of the next band and errors on that row are
+/// ignored there, so both bands have the same vertices on it and the mesh has no cracks.
+/// Every pixel is still within `max_error`, but refinement of a band doesn't see the rest
+/// of the grid, so grids taller than one band get more vertices than from [`triangulate`].
+/// Shorter grids are a single band and give exactly the same mesh.
+///
+/// # Arguments
+///
+/// * `reader` - Source of the grid rows.
+/// * `width` - The width of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Errors
+///
+/// - `Io` - If reading from the reader fails.
+/// - `IncompleteRowError` - If the data ends in the middle of a row.
+/// - Same as [`triangulate`] otherwise, with indices of the whole grid.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::Cursor;
+/// use delatin::{triangulate, triangulate_from_reader, Error};
+///
+/// let heights: Vec<f64> = vec![100.1, 123.4, 111.5, 121.4, 101.9, 115.3];
+/// let bytes = heights
+///     .iter()
+///     .flat_map(|height| height.to_le_bytes())
+///     .collect::<Vec<u8>>();
+///
+/// let streamed = triangulate_from_reader(Cursor::new(bytes), 2, Error(1.0))?;
+/// assert_eq!(streamed, triangulate(&heights, (2, 3), Error(1.0))?);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_from_reader<R: Read>(
+    mut reader: R,
+    width: usize,
+    max_error: Error,
+) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    let mut mesh = BandedMesh::default();
+    let mut height_data = Vec::new();
+    let mut row = vec![0; width * size_of::<f64>()];
+
+    while read_row(&mut reader, &mut row)? {
+        height_data
+            .extend(row.chunks_exact(size_of::<f64>()).map(|bytes| {
+                f64::from_le_bytes(bytes.try_into().expect("Chunk has size of f64."))
+            }));
+        if height_data.len() == (READER_BAND_ROWS + 1) * width {
+            mesh.add_band(&height_data, width, max_error)?;
+            height_data.drain(..READER_BAND_ROWS * width);
+        }
+    }
+
+    // the last row of a finished band is already triangulated
+    if mesh.start == 0 || height_data.len() > width {
+        mesh.add_band(&height_data, width, max_error)?;
+    }
+
+    Ok((mesh.points, mesh.triangles))
+}
+
+/// Fills the row from the reader, retrying interrupted reads.
+///
+/// Returns `false` if the reader is exhausted before the row starts.
+fn read_row<R: Read>(reader: &mut R, row: &mut [u8]) -> Result<bool, TriangulationError> {
+    let mut filled = 0;
+    while filled < row.len() {
+        match reader.read(&mut row[filled..]) {
+            Ok(0) => break,
+            Ok(read) => filled += read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    if filled > 0 && filled < row.len() {
+        return Err(TriangulationError::IncompleteRowError);
+    }
+
+    Ok(filled > 0)
+}
+
+/// Mesh of the bands triangulated so far by [`triangulate_from_reader`].
+#[derive(Default)]
+struct BandedMesh {
+    /// Grid row of the first row of the next band
+    start: usize,
+    /// Vertices of the whole grid
+    points: Vec<Point>,
+    /// Triangles of the whole grid
+    triangles: Vec<Triangle>,
+    /// Column and index of vertices on the last row of the previous band, sorted by column
+    shared_vertices: Vec<(usize, usize)>,
+}
+
+impl BandedMesh {
+    /// Triangulates the next band, whose first row is the last row of the previous band.
+    fn add_band(
+        &mut self,
+        height_data: &[f64],
+        width: usize,
+        max_error: Error,
+    ) -> Result<(), TriangulationError> {
+        let height = height_data.len().checked_div(width).unwrap_or(0);
+        let seed_points = self
+            .shared_vertices
+            .iter()
+            .map(|&(x, _)| (x, 0))
+            .collect::<Vec<Point>>();
+        // the shared row is already within the max error, ignoring it keeps its vertices
+        let confidence = (0..height_data.len())
+            .map(|index| if index < width { 0.0 } else { 1.0 })
+            .collect::<Vec<f64>>();
+        let mut options = TriangulationOptions::new()
+            .max_error(max_error)
+            .seed_points(&seed_points);
+        if self.start > 0 {
+            options = options.confidence(&confidence);
+        }
+        let (points, triangles) =
+            options
+                .run(height_data, width, height)
+                .map_err(|error| match error {
+                    TriangulationError::NonFiniteHeight { index } => {
+                        TriangulationError::NonFiniteHeight {
+                            index: index + self.start * width,
+                        }
+                    }
+                    error => error,
+                })?;
+
+        let mut last_row_vertices = Vec::new();
+        let indices = points
+            .iter()
+            .map(|&(x, y)| {
+                let shared_vertex = self
+                    .shared_vertices
+                    .binary_search_by_key(&x, |&(column, _)| column)
+                    .ok()
+                    .filter(|_| y == 0 && self.start > 0);
+                let index = shared_vertex.map_or_else(
+                    || {
+                        self.points.push((x, self.start + y));
+                        self.points.len() - 1
+                    },
+                    |position| self.shared_vertices[position].1,
+                );
+                if y + 1 == height {
+                    last_row_vertices.push((x, index));
+                }
+
+                index
+            })
+            .collect::<Vec<usize>>();
+        self.triangles.extend(
+            triangles
+                .iter()
+                .map(|&(a, b, c)| (indices[a], indices[b], indices[c])),
+        );
+
+        last_row_vertices.sort_unstable();
+        self.shared_vertices = last_row_vertices;
+        self.start += height - 1;
+
+        Ok(())
+    }
+}
+
+/// Suggests max error for which exported mesh takes roughly `target_bytes`.
+///
+/// The grid is triangulated with max error starting at half of the height range and halving
+/// on each of the probes, until exported size reaches the target. The result is linearly
+/// interpolated between the two closest probes, so it is only an approximation.
+/// If even the finest probe doesn't reach the target, its error is returned.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `target_bytes` - Desired size of exported mesh.
+/// * `format` - Format used to measure the size.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::export::{write_obj, ExportFormat};
+/// use delatin::{estimate_error_for_size, triangulate};
+///
+/// let size = 65;
+/// let heights = (0..size * size)
+///     .map(|i| ((i % size) as f64 / 5.0).sin() * ((i / size) as f64 / 7.0).cos() * 100.0)
+///     .collect::<Vec<f64>>();
+/// let target_bytes = 20_000;
+///
+/// let max_error = estimate_error_for_size(&heights, (size, size), target_bytes, ExportFormat::Obj)?;
+///
+/// let (points, triangles) = triangulate(&heights, (size, size), max_error)?;
+/// let mut obj = Vec::new();
+/// write_obj(&mut obj, &points, &triangles, &heights, size).unwrap();
+/// assert!(obj.len() > target_bytes / 2 && obj.len() < target_bytes * 3 / 2);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn estimate_error_for_size(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    target_bytes: usize,
+    format: ExportFormat,
+) -> Result<Error, TriangulationError> {
+    let (min_height, max_height) = height_data.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(min_height, max_height), &height| (min_height.min(height), max_height.max(height)),
+    );
+    let height_range = max_height - min_height;
+    if height_range <= 0.0 {
+        return Ok(Error(0.0));
+    }
+
+    let mut probe_error = height_range / 2.0;
+    // previous probe error and size, its size is always below the target
+    let mut previous_probe: Option<(f64, usize)> = None;
+    for _ in 0..SIZE_ESTIMATION_PROBES {
+        let (points, triangles) = triangulate(height_data, dimesions, Error(probe_error))?;
+        let size = format.size(&points, &triangles, height_data, dimesions.0);
+
+        if size >= target_bytes {
+            let Some((previous_error, previous_size)) = previous_probe else {
+                return Ok(Error(probe_error));
+            };
+            let ratio = (target_bytes - previous_size) as f64 / (size - previous_size) as f64;
+
+            return Ok(Error(
+                previous_error + (probe_error - previous_error) * ratio,
+            ));
+        }
+
+        previous_probe = Some((probe_error, size));
+        probe_error /= 2.0;
+    }
+
+    Ok(Error(probe_error * 2.0))
+}
+
+/// Triangulates the grid to roughly `target_triangles` triangles, searching for the max error.
+///
+/// Max error is bisected between zero and the height range, with a full triangulation per
+/// probe, until the triangle count is within `tolerance` of the target or
+/// the probes run out. The mesh closest to the target is returned then. Triangle count
+/// grows in steps, so a very small tolerance may not be reachable.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `target_triangles` - Desired number of triangles.
+/// * `tolerance` - Allowed difference between the triangle count and the target.
+///
+/// # Returns
+///
+/// Tuple of the mesh and the max error it was triangulated with, e.g. to reuse it
+/// for neighbouring tiles.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_mesh, triangulate_to_budget};
+///
+/// let heights = (0..65 * 65)
+///     .map(|i| ((i % 65) as f64 / 5.0).sin() * ((i / 65) as f64 / 7.0).cos() * 100.0)
+///     .collect::<Vec<f64>>();
+///
+/// let (mesh, max_error) = triangulate_to_budget(&heights, (65, 65), 1000, 50)?;
+/// assert!(mesh.triangles().len().abs_diff(1000) <= 50);
+/// assert_eq!(triangulate_mesh(&heights, (65, 65), max_error)?, mesh);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_to_budget(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    target_triangles: usize,
+    tolerance: usize,
+) -> Result<(Mesh, Error), TriangulationError> {
+    let (min_height, max_height) = height_data.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(min_height, max_height), &height| (min_height.min(height), max_height.max(height)),
+    );
+    let height_range = max_height - min_height;
+    if height_range <= 0.0 {
+        // flat grid has the same mesh for any max error
+        return Ok((
+            triangulate_mesh(height_data, dimesions, Error(0.0))?,
+            Error(0.0),
+        ));
+    }
+
+    let (mut low_error, mut high_error) = (0.0, height_range);
+    let mut closest: Option<(Mesh, Error, usize)> = None;
+    for _ in 0..BUDGET_SEARCH_PROBES {
+        let probe_error = (low_error + high_error) / 2.0;
+        let mesh = triangulate_mesh(height_data, dimesions, Error(probe_error))?;
+        let triangle_count = mesh.triangles_flat().len() / 3;
+        let distance = triangle_count.abs_diff(target_triangles);
+
+        // larger error gives fewer triangles
+        if triangle_count > target_triangles {
+            low_error = probe_error;
+        } else {
+            high_error = probe_error;
+        }
+        if closest
+            .as_ref()
+            .is_none_or(|(_, _, closest_distance)| distance < *closest_distance)
+        {
+            closest = Some((mesh, Error(probe_error), distance));
+        }
+        if distance <= tolerance {
+            break;
+        }
+    }
+
+    let (mesh, max_error, _) = closest.expect("At least one probe is done.");
+
+    Ok((mesh, max_error))
+}
+
+/// Runs the triangulation process once, taking a mesh for every threshold, e.g. for levels
+/// of detail.
+///
+/// Refinement only adds vertices, so a finer mesh contains all the refinement of a coarser
+/// one. Thresholds are visited from the largest to the smallest and the mesh is taken as soon
+/// as the maximum error is not above the threshold, so every mesh is the same as returned by
+/// [`triangulate_mesh`] for its threshold, at the cost of the finest one only.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `dimesions` - Tuple width and height of the grid.
+/// * `thresholds` - The maximum allowable errors of the meshes, in any order.
+///
+/// # Returns
+///
+/// A mesh for every threshold, in the order of `thresholds`.
+///
+/// # Errors
+///
+/// Same as [`triangulate`].
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_lod, triangulate_mesh, Error};
+///
+/// let heights = (0..65 * 65)
+///     .map(|i| ((i % 65) as f64 / 5.0).sin() * ((i / 65) as f64 / 7.0).cos() * 100.0)
+///     .collect::<Vec<f64>>();
+/// let thresholds = [Error(8.0), Error(1.0), Error(4.0), Error(2.0)];
+///
+/// let levels = triangulate_lod(&heights, (65, 65), &thresholds)?;
+/// assert_eq!(levels.len(), 4);
+/// for (mesh, &max_error) in levels.iter().zip(&thresholds) {
+///     assert!(mesh.max_error() <= max_error);
+///     assert_eq!(*mesh, triangulate_mesh(&heights, (65, 65), max_error)?);
+/// }
+/// assert!(levels[1].points().len() > levels[3].points().len());
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn triangulate_lod(
+    height_data: &[f64],
+    dimesions: (usize, usize),
+    thresholds: &[Error],
+) -> Result<Vec<Mesh>, TriangulationError> {
+    let mut order = (0..thresholds.len()).collect::<Vec<usize>>();
+    order.sort_by(|&a, &b| thresholds[b].0.total_cmp(&thresholds[a].0));
+
+    let mut refiner = Refiner::new(height_data, dimesions)?;
+    let mut levels = vec![Mesh::default(); thresholds.len()];
+    for index in order {
+        refiner.refine(thresholds[index])?;
+        levels[index] = refiner.mesh();
+    }
+
+    Ok(levels)
+}
+
+/// Runs the triangulation process of every tile on the rayon thread pool.
+///
+/// Tiles are triangulated independently, so every mesh is the same as returned by
+/// [`triangulate_mesh`] for its tile, regardless of thread count and scheduling.
+///
+/// Available with the `rayon` feature.
+///
+/// # Arguments
+///
+/// * `tiles` - Height data and dimensions of the tiles.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Returns
+///
+/// A mesh for every tile, in the order of `tiles`.
+///
+/// # Errors
+///
+/// Same as [`triangulate`], the error of the first failed tile in the order of `tiles`.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_mesh, triangulate_tiles, Error, TileRef};
+///
+/// let tiles = (0..4)
+///     .map(|tile| {
+///         (0..33 * 17)
+///             .map(|i| ((i % 33 + tile * 32) as f64 / 5.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+///             .collect::<Vec<f64>>()
+///     })
+///     .collect::<Vec<Vec<f64>>>();
+/// let tile_refs = tiles
+///     .iter()
+///     .map(|tile| TileRef::new(tile, (33, 17)))
+///     .collect::<Vec<TileRef>>();
+///
+/// let meshes = triangulate_tiles(&tile_refs, Error(1.0))?;
+/// for (mesh, tile) in meshes.iter().zip(&tiles) {
+///     assert_eq!(*mesh, triangulate_mesh(tile, (33, 17), Error(1.0))?);
+/// }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[cfg(feature = "rayon")]
+pub fn triangulate_tiles(
+    tiles: &[TileRef<'_>],
+    max_error: Error,
+) -> Result<Vec<Mesh>, TriangulationError> {
+    use rayon::prelude::*;
+
+    let meshes = tiles
+        .par_iter()
+        .map(|tile| triangulate_mesh(tile.height_data, tile.dimensions, max_error))
+        .collect::<Vec<Result<Mesh, TriangulationError>>>();
+
+    meshes.into_iter().collect()
+}
+
+/// Shrinks the grid by an integer factor, averaging every `factor` x `factor` block of
+/// heights into one, e.g. before a coarse triangulation of a huge grid.
+///
+/// Dimensions which are not divisible by the factor are rounded up and the partial blocks
+/// at the right and bottom edges are averaged over the pixels they have, so no pixels are
+/// cropped. Pixel `(x, y)` of the result covers pixels from `(x * factor, y * factor)`
+/// of the original grid, so points triangulated from it are multiplied by the factor to get
+/// the original coordinates, which are at the top left corner of their block, not its center.
+/// Factor `0` is treated as `1`.
+///
+/// # Arguments
+///
+/// * `height_data` - Height values of the grid.
+/// * `width` - The width of the grid.
+/// * `height` - The height of the grid.
+/// * `factor` - How many pixels in x and y are averaged into one.
+///
+/// # Returns
+///
+/// Tuple of averaged heights, width and height of the smaller grid.
+///
+/// # Errors
+///
+/// - `InvalidDataLengthError` - If the length of the height data does not match the width and height of the grid.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{downsample, triangulate, Error};
+///
+/// // 5x3 grid, the last column and row are partial blocks
+/// let heights = vec![
+///     1.0, 3.0, 5.0, 7.0, 9.0,
+///     1.0, 3.0, 5.0, 7.0, 9.0,
+///     2.0, 2.0, 2.0, 2.0, 2.0,
+/// ];
+/// let (small, width, height) = downsample(&heights, 5, 3, 2)?;
+/// assert_eq!((width, height), (3, 2));
+/// assert_eq!(small, vec![2.0, 6.0, 9.0, 2.0, 2.0, 2.0]);
+///
+/// let (points, _) = triangulate(&small, (width, height), Error(0.5))?;
+/// // points of the small grid map back to the top left pixels of their blocks
+/// assert!(points.iter().all(|&(x, y)| x * 2 <= 4 && y * 2 <= 2));
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub fn downsample<H: HeightValue>(
+    height_data: &[H],
+    width: usize,
+    height: usize,
+    factor: usize,
+) -> Result<(Vec<f64>, usize, usize), TriangulationError> {
+    (height_data.len() == width * height)
+        .then_some(())
+        .ok_or(TriangulationError::InvalidDataLengthError)?;
+
+    let factor = factor.max(1);
+    let small_width = width.div_ceil(factor);
+    let small_height = height.div_ceil(factor);
+    let mut sums = vec![0.0; small_width * small_height];
+    let mut counts = vec![0usize; small_width * small_height];
+    for y in 0..height {
+        for x in 0..width {
+            let index = y / factor * small_width + x / factor;
+            sums[index] += height_data[y * width + x].to_f64();
+            counts[index] += 1;
+        }
+    }
+
+    let averages = sums
+        .into_iter()
+        .zip(counts)
+        .map(|(sum, count)| sum / count as f64)
+        .collect();
+
+    Ok((averages, small_width, small_height))
+}
+
+fn check_finite_heights<H: HeightValue>(
+    height_data: &[H],
+    nodata: Option<f64>,
+) -> Result<(), TriangulationError> {
+    match height_data.iter().position(|height| {
+        let height = height.to_f64();
+        !height.is_finite() && !nodata.is_some_and(|nodata| is_nodata_value(height, nodata))
+    }) {
+        Some(index) => Err(TriangulationError::NonFiniteHeight { index }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that the grid has four distinct corners to start the triangulation from.
+/// Empty grids would underflow when placing them, single rows or columns give collinear ones.
+/// Also checks that indices of any mesh of the grid fit the stored index type.
+fn check_grid_size(width: usize, height: usize) -> Result<(), TriangulationError> {
+    (width >= 2 && height >= 2)
+        .then_some(())
+        .ok_or(TriangulationError::GridTooSmall { width, height })?;
+    width
+        .checked_mul(height)
+        .is_some_and(|pixels| pixels <= MAX_PIXELS)
+        .then_some(())
+        .ok_or(TriangulationError::GridTooLarge { width, height })
+}
+
+fn create_triangulation<H: HeightValue, M: ErrorMetric>(
+    height_data: &[H],
+    dimesions: (usize, usize),
+    metric: M,
+) -> Result<Triangulation<'_, H, M>, TriangulationError> {
     let width = dimesions.0;
     let height = dimesions.1;
+    check_grid_size(width, height)?;
     (height_data.len() == width * height)
         .then_some(())
         .ok_or(TriangulationError::InvalidDataLengthError)?;
 
-    let mut delatin = Triangulation::new(height_data, width, height);
-
-    delatin.run(max_error)
+    Ok(Triangulation::with_metric(
+        height_data,
+        width,
+        height,
+        metric,
+    ))
 }
@@ -0,0 +1,2023 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::export;
+use crate::predicates::circumcircle;
+use crate::utils::{
+    barycentric_weights, compute_vertex_normals, for_each_triangle_pixel, get_signed_area,
+    is_nodata_value,
+};
+use crate::{Error, Height, HeightValue, Point, PointWithHeight, Triangle};
+
+/// Triangulated mesh with its half-edge connectivity.
+///
+/// Methods reading heights take the grid the mesh was built from, of any [`HeightValue`] type.
+///
+/// With the `serde` feature mesh can be serialized, e.g. to cache triangulation results.
+/// Only points, triangles and max error are stored. Half-edges are restored from shared
+/// edges on deserialization, which fails if triangles don't form triplets of valid
+/// vertex indices.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "serde")]
+/// # {
+/// use delatin::{triangulate_mesh, Error, Mesh};
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+///
+/// let json = serde_json::to_string(&mesh).unwrap();
+/// assert!(!json.contains("half_edges"));
+/// let cached: Mesh = serde_json::from_str(&json).unwrap();
+/// assert_eq!(cached, mesh);
+///
+/// let broken = r#"{"points":[[0,0],[1,0],[0,1]],"triangles":[0,1,3],"max_error":0.0}"#;
+/// assert!(serde_json::from_str::<Mesh>(broken).is_err());
+/// # }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MeshData"))]
+pub struct Mesh {
+    /// Points of vertices
+    points: Vec<Point>,
+    /// A vector of vertex points indices, triplets of these indices form triangles.
+    triangles: Vec<usize>,
+    /// A vector halfedge indices
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
+    half_edges: Vec<Option<usize>>,
+    /// Max error of triangles reported by the triangulation
+    max_error: Error,
+}
+
+/// Serialized fields of [`Mesh`], checked before half-edges are restored from them.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MeshData {
+    points: Vec<Point>,
+    triangles: Vec<usize>,
+    max_error: Error,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<MeshData> for Mesh {
+    type Error = &'static str;
+
+    fn try_from(data: MeshData) -> Result<Self, Self::Error> {
+        if data.triangles.len() % 3 != 0 {
+            return Err("triangle vertices don't form triplets");
+        }
+        if data
+            .triangles
+            .iter()
+            .any(|&vertex| vertex >= data.points.len())
+        {
+            return Err("triangle vertex index is out of range");
+        }
+        let half_edges = find_half_edges(&data.triangles);
+
+        Ok(Self::new(
+            data.points,
+            data.triangles,
+            half_edges,
+            data.max_error,
+        ))
+    }
+}
+
+impl Mesh {
+    pub(crate) fn new(
+        points: Vec<Point>,
+        triangles: Vec<usize>,
+        half_edges: Vec<Option<usize>>,
+        max_error: Error,
+    ) -> Self {
+        Self {
+            points,
+            triangles,
+            half_edges,
+            max_error,
+        }
+    }
+
+    /// Creates a mesh from vertices and triangles, e.g. returned by [`crate::triangulate`].
+    ///
+    /// Connectivity of triangles is restored from shared edges. Max error of such mesh
+    /// is unknown and set to zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - Vertices of the mesh.
+    /// * `triangles` - Triangles of the mesh, each defined by indices into the `points`.
+    pub fn from_triangles(points: Vec<Point>, triangles: &[Triangle]) -> Self {
+        let triangles = triangles
+            .iter()
+            .flat_map(|&(a, b, c)| [a, b, c])
+            .collect::<Vec<usize>>();
+        let half_edges = find_half_edges(&triangles);
+
+        Self::new(points, triangles, half_edges, Error(0.0))
+    }
+
+    /// Vertices of the mesh. Each point corresponds to heights vector index.
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Triangles of the mesh, each defined by indices into the `points`.
+    pub fn triangles(&self) -> Vec<Triangle> {
+        self.iter_triangles().collect()
+    }
+
+    /// Vertices of the mesh as flat indices `y * width + x` into the height data, with
+    /// triangles referencing positions in that vector, the same as in [`Mesh::triangles`].
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the grid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4, 98.7, 105.2];
+    /// let mesh = triangulate_mesh(&heights, (3, 2), Error(0.0))?;
+    ///
+    /// let (indices, triangles) = mesh.to_flat_indices(3);
+    /// for (&index, &(x, y)) in indices.iter().zip(mesh.points()) {
+    ///     assert_eq!(heights[index], heights[y * 3 + x]);
+    /// }
+    /// assert_eq!(triangles, mesh.triangles());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn to_flat_indices(&self, width: usize) -> (Vec<usize>, Vec<Triangle>) {
+        let indices = self.points.iter().map(|&(x, y)| y * width + x).collect();
+
+        (indices, self.triangles())
+    }
+
+    /// Number of vertices of the mesh.
+    pub fn point_count(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Number of triangles of the mesh, without collecting them like [`Mesh::triangles`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    /// let (points, triangles) = triangulate(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// assert_eq!(mesh.point_count(), points.len());
+    /// assert_eq!(mesh.triangle_count(), triangles.len());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len() / 3
+    }
+
+    /// Iterates over vertices of the mesh, same as [`Mesh::points`] by value.
+    pub fn iter_points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.points.iter().copied()
+    }
+
+    /// Iterates over triangles of the mesh without collecting them like [`Mesh::triangles`],
+    /// e.g. for writing large meshes triangle by triangle.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// let mut obj = String::new();
+    /// for (a, b, c) in mesh.iter_triangles() {
+    ///     obj.push_str(&format!("f {} {} {}\n", a + 1, b + 1, c + 1));
+    /// }
+    /// assert_eq!(obj.lines().count(), mesh.triangles().len());
+    /// assert!(mesh.iter_points().eq(mesh.points().iter().copied()));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn iter_triangles(&self) -> impl Iterator<Item = Triangle> + '_ {
+        self.triangles
+            .chunks_exact(3)
+            .map(|triangle| (triangle[0], triangle[1], triangle[2]))
+    }
+
+    /// Writes the mesh as Wavefront OBJ, see [`export::write_obj`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// let mut obj = Vec::new();
+    /// mesh.write_obj(&mut obj, &heights, 2).unwrap();
+    /// let obj = String::from_utf8(obj).unwrap();
+    /// assert_eq!(obj.lines().filter(|line| line.starts_with("v ")).count(), 4);
+    /// assert_eq!(obj.lines().filter(|line| line.starts_with("f ")).count(), 2);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn write_obj<H: HeightValue, W: Write>(
+        &self,
+        writer: W,
+        height_data: &[H],
+        width: usize,
+    ) -> io::Result<()> {
+        export::write_obj(writer, &self.points, &self.triangles(), height_data, width)
+    }
+
+    /// Writes the mesh as binary STL without z offset, see [`export::write_stl_binary`].
+    pub fn write_stl_binary<H: HeightValue, W: Write>(
+        &self,
+        writer: W,
+        height_data: &[H],
+        width: usize,
+    ) -> io::Result<()> {
+        export::write_stl_binary(
+            writer,
+            &self.points,
+            &self.triangles(),
+            height_data,
+            width,
+            0.0,
+        )
+    }
+
+    /// Writes the mesh as PLY without z offset, see [`export::write_ply`].
+    pub fn write_ply<H: HeightValue, W: Write>(
+        &self,
+        writer: W,
+        height_data: &[H],
+        width: usize,
+        ascii: bool,
+    ) -> io::Result<()> {
+        export::write_ply(
+            writer,
+            &self.points,
+            &self.triangles(),
+            height_data,
+            width,
+            ascii,
+            0.0,
+        )
+    }
+
+    /// Writes edges of the mesh as SVG, see [`export::write_svg`].
+    pub fn write_svg<W: Write>(&self, writer: W, width: usize, height: usize) -> io::Result<()> {
+        export::write_svg(writer, &self.points, &self.triangles(), width, height)
+    }
+
+    /// Max error of the mesh as reported by the triangulation.
+    pub fn max_error(&self) -> Error {
+        self.max_error
+    }
+
+    /// Vertices of the mesh as stored internally, same as [`Mesh::points`].
+    pub fn vertices_flat(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// Triangles of the mesh as stored internally, without repackaging into tuples.
+    ///
+    /// Every three consecutive values are indices into the points of one triangle,
+    /// so triangle `i` is at `3 * i..3 * i + 3`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// let triangles = mesh.triangles();
+    /// assert_eq!(mesh.triangles_flat().len(), 3 * triangles.len());
+    /// assert_eq!(&mesh.triangles_flat()[3..6], &[triangles[1].0, triangles[1].1, triangles[1].2]);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn triangles_flat(&self) -> &[usize] {
+        &self.triangles
+    }
+
+    /// Half-edges of the mesh, parallel to [`Mesh::triangles_flat`].
+    ///
+    /// Half-edge `e` goes from vertex `triangles_flat()[e]` to the next vertex of the same
+    /// triangle, so edges of triangle `i` are `3 * i` (a to b), `3 * i + 1` (b to c) and
+    /// `3 * i + 2` (c to a). Its value is the opposite half-edge of the adjacent triangle
+    /// sharing the edge, or `None` on the boundary of the mesh. Triangle of half-edge `e`
+    /// is `e / 3`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// let triangles = mesh.triangles_flat();
+    /// for (edge, opposite) in mesh.half_edges().iter().enumerate() {
+    ///     if let Some(opposite) = *opposite {
+    ///         assert_eq!(mesh.half_edges()[opposite], Some(edge));
+    ///         // opposite half-edge goes the other way
+    ///         let next = |edge: usize| if edge % 3 == 2 { edge - 2 } else { edge + 1 };
+    ///         assert_eq!(triangles[edge], triangles[next(opposite)]);
+    ///         assert_eq!(triangles[next(edge)], triangles[opposite]);
+    ///     }
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn half_edges(&self) -> &[Option<usize>] {
+        &self.half_edges
+    }
+
+    /// Triangles adjacent to the triangle across its edges a to b, b to c and c to a.
+    ///
+    /// Indices are triangle indices, the same as in [`Mesh::triangles`]. Edges on the
+    /// boundary of the mesh have no neighbour.
+    ///
+    /// # Arguments
+    ///
+    /// * `triangle_index` - Index of the triangle.
+    ///
+    /// # Panics
+    ///
+    /// If the triangle index is out of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// // two triangles of a square share the diagonal
+    /// let neighbors = mesh.neighbors(0);
+    /// assert_eq!(neighbors.iter().flatten().collect::<Vec<_>>(), vec![&1]);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn neighbors(&self, triangle_index: usize) -> [Option<usize>; 3] {
+        [0, 1, 2]
+            .map(|edge| self.half_edges[3 * triangle_index + edge].map(|opposite| opposite / 3))
+    }
+
+    /// Bounding box of the vertices, which may not span the whole grid, e.g. for a mesh
+    /// made from a part of triangles. Empty mesh has zero bounds.
+    ///
+    /// # Returns
+    ///
+    /// Tuple of min and max points, both inclusive.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, Error, Mesh};
+    ///
+    /// let heights = (0..9 * 9).map(|i| ((i % 9) * (i / 9)) as f64).collect::<Vec<f64>>();
+    /// let (points, triangles) = triangulate(&heights, (9, 9), Error(0.5))?;
+    /// assert_eq!(Mesh::from_triangles(points.clone(), &triangles).bounds(), ((0, 0), (8, 8)));
+    ///
+    /// // triangles with only vertices in the left half of the grid
+    /// let left = triangles
+    ///     .into_iter()
+    ///     .filter(|&(a, b, c)| [a, b, c].iter().all(|&vertex| points[vertex].0 <= 4))
+    ///     .collect::<Vec<_>>();
+    /// let used = left.iter().flat_map(|&(a, b, c)| [points[a], points[b], points[c]]);
+    /// let ((_, _), (max_x, _)) = Mesh::from_triangles(used.collect(), &[]).bounds();
+    /// assert!(max_x <= 4);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn bounds(&self) -> (Point, Point) {
+        let Some(&first) = self.points.first() else {
+            return ((0, 0), (0, 0));
+        };
+
+        self.points
+            .iter()
+            .fold((first, first), |(min, max), point| {
+                (
+                    (min.0.min(point.0), min.1.min(point.1)),
+                    (max.0.max(point.0), max.1.max(point.1)),
+                )
+            })
+    }
+
+    /// Same as [`Mesh::bounds`] with the range of vertex heights, e.g. for framing the camera
+    /// of a renderer. Heights between vertices are interpolated, so they are within the range.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    ///
+    /// # Returns
+    ///
+    /// Tuple of min and max corners as x, y and height.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// assert_eq!(mesh.bounds_3d(&heights, 2), ((0, 0, 100.1), (1, 1, 123.4)));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn bounds_3d<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+    ) -> ((usize, usize, Height), (usize, usize, Height)) {
+        let (min, max) = self.bounds();
+        let (min_height, max_height) = self
+            .points
+            .iter()
+            .map(|point| height_data[point.1 * width + point.0].to_f64())
+            .fold(None, |range: Option<(Height, Height)>, height| {
+                Some(range.map_or((height, height), |(low, high)| {
+                    (low.min(height), high.max(height))
+                }))
+            })
+            .unwrap_or_default();
+
+        ((min.0, min.1, min_height), (max.0, max.1, max_height))
+    }
+
+    /// Vertices of the mesh in world units of a grid with the cell size, e.g. meters.
+    ///
+    /// # Arguments
+    ///
+    /// * `cell_size` - Ground size of a pixel in x and y.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// let points = mesh.scaled_points((30.0, 10.0));
+    /// assert_eq!(points[2], (30.0, 10.0));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn scaled_points(&self, cell_size: (f64, f64)) -> Vec<(f64, f64)> {
+        self.points
+            .iter()
+            .map(|point| (point.0 as f64 * cell_size.0, point.1 as f64 * cell_size.1))
+            .collect()
+    }
+
+    /// Georeferenced vertices of the mesh with their height sampled from the grid.
+    ///
+    /// Pixel `(col, row)` is mapped by the six-parameter affine transform, as stored with
+    /// GeoTIFF or returned by GDAL, to `x = a + col * b + row * c` and
+    /// `y = d + col * e + row * f`. Rotation terms `c` and `e` are zero for north-up rasters,
+    /// which needs no special handling.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `geotransform` - Coefficients `[a, b, c, d, e, f]` of the transform.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(x, y, z)` vertices in the same order as `points`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// // north-up raster with 30 m pixels, origin at its top left corner
+    /// let vertices = mesh.to_world(&heights, 2, [500000.0, 30.0, 0.0, 4100000.0, 0.0, -30.0]);
+    /// assert_eq!(vertices[2], (500030.0, 4099970.0, 121.4));
+    ///
+    /// // raster rotated by 90 degrees
+    /// let vertices = mesh.to_world(&heights, 2, [0.0, 0.0, 1.0, 0.0, 1.0, 0.0]);
+    /// assert_eq!(vertices[1], (0.0, 1.0, 123.4));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn to_world<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        geotransform: [f64; 6],
+    ) -> Vec<(f64, f64, f64)> {
+        let [a, b, c, d, e, f] = geotransform;
+        self.points
+            .iter()
+            .map(|&(col, row)| {
+                let height = height_data[row * width + col].to_f64();
+                let (col, row) = (col as f64, row as f64);
+
+                (a + col * b + row * c, d + col * e + row * f, height)
+            })
+            .collect()
+    }
+
+    /// Vertices of the mesh moved so the center of the grid is at the origin, e.g. for placing
+    /// the model at the world origin of a renderer.
+    ///
+    /// `((width - 1) / 2, (height - 1) / 2)` is subtracted from every vertex, so coordinates
+    /// may be fractional and negative. Heights are kept as sampled from the grid, unless
+    /// `center_elevation` is set, then the middle of the heights of vertices is subtracted too.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `center_elevation` - Whether heights are centered as well.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(x, y, z)` vertices in the same order as `points`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.0, 120.0, 110.0, 130.0, 140.0, 125.0];
+    /// let mesh = triangulate_mesh(&heights, (3, 2), Error(0.0))?;
+    ///
+    /// let vertices = mesh.recenter(&heights, 3, false);
+    /// assert!(vertices.contains(&(-1.0, -0.5, 100.0)));
+    /// assert!(vertices.contains(&(1.0, 0.5, 125.0)));
+    ///
+    /// let vertices = mesh.recenter(&heights, 3, true);
+    /// assert!(vertices.contains(&(-1.0, -0.5, -20.0)));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn recenter<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        center_elevation: bool,
+    ) -> Vec<(f64, f64, f64)> {
+        let height = height_data.len().checked_div(width).unwrap_or(0);
+        let center_x = width.saturating_sub(1) as f64 / 2.0;
+        let center_y = height.saturating_sub(1) as f64 / 2.0;
+        let center_z = if center_elevation {
+            let ((_, _, min_height), (_, _, max_height)) = self.bounds_3d(height_data, width);
+            (min_height + max_height) / 2.0
+        } else {
+            0.0
+        };
+
+        self.points
+            .iter()
+            .map(|point| {
+                let height = height_data[point.1 * width + point.0].to_f64();
+                (
+                    point.0 as f64 - center_x,
+                    point.1 as f64 - center_y,
+                    height - center_z,
+                )
+            })
+            .collect()
+    }
+
+    /// Vertices of the mesh with their height sampled from the grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `z_offset` - Constant added to every sampled height, e.g. a datum shift.
+    ///   It is applied to the raw grid sample, there is no scaling or normalization before it.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(x, y, z)` vertices in the same order as `points`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.1, 123.4, 111.5, 121.4];
+    /// let mesh = triangulate_mesh(&heights, (2, 2), Error(1.0))?;
+    ///
+    /// let vertices = mesh.vertices_3d(&heights, 2, 0.0);
+    /// let shifted = mesh.vertices_3d(&heights, 2, 100.0);
+    /// for (vertex, shifted) in vertices.iter().zip(&shifted) {
+    ///     assert_eq!(shifted.2, vertex.2 + 100.0);
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn vertices_3d<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        z_offset: f64,
+    ) -> Vec<(f64, f64, f64)> {
+        self.points
+            .iter()
+            .map(|point| {
+                let height = height_data[point.1 * width + point.0].to_f64();
+                (point.0 as f64, point.1 as f64, height + z_offset)
+            })
+            .collect()
+    }
+
+    /// Groups triangles by elevation bands, e.g. for hypsometric tinting.
+    ///
+    /// Triangle belongs to the band of the mean height of its three vertices,
+    /// so triangles spanning a band boundary are assigned by their mean.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `boundaries` - Ascending heights separating the bands.
+    ///
+    /// # Returns
+    ///
+    /// Vector of `boundaries.len() + 1` bands with triangle indices. Band `i` has triangles with
+    /// mean height below `boundaries[i]` and not below `boundaries[i - 1]`, the last band has the rest.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// // ramp rising along x
+    /// let heights = (0..9 * 9).map(|i| (i % 9) as f64).collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (9, 9), Error(0.0))?;
+    ///
+    /// let bands = mesh.elevation_bands(&heights, 9, &[4.0]);
+    /// assert_eq!(bands.len(), 2);
+    /// assert!(!bands[0].is_empty() && !bands[1].is_empty());
+    /// assert_eq!(bands[0].len() + bands[1].len(), mesh.triangles().len());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn elevation_bands<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        boundaries: &[f64],
+    ) -> Vec<Vec<usize>> {
+        let mut bands = vec![Vec::new(); boundaries.len() + 1];
+        for (triangle_index, triangle) in self.triangles.chunks_exact(3).enumerate() {
+            let mean_height = triangle
+                .iter()
+                .map(|&vertex| {
+                    let point = self.points[vertex];
+                    height_data[point.1 * width + point.0].to_f64()
+                })
+                .sum::<f64>()
+                / 3.0;
+
+            bands[boundaries.partition_point(|&boundary| boundary <= mean_height)]
+                .push(triangle_index);
+        }
+
+        bands
+    }
+
+    /// Smooth normals of vertices for lighting, e.g. to pass as a vertex attribute.
+    ///
+    /// Normal of a vertex is the average of normals of its triangles weighted by their area,
+    /// with heights taken from the grid. Boundary vertices get normals from the triangles
+    /// they have. Normals are unit length and point up (positive z), vertices without
+    /// triangles get zero normal.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `[x, y, z]` normals in the same order as `points`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// // plane z = x rising to the right
+    /// let heights = (0..25).map(|i| (i % 5) as f64).collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (5, 5), Error(0.0))?;
+    ///
+    /// let normals = mesh.vertex_normals(&heights, 5);
+    /// assert_eq!(normals.len(), mesh.points().len());
+    /// for normal in normals {
+    ///     assert!((normal[0] + std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    ///     assert!(normal[1].abs() < 1e-6);
+    ///     assert!((normal[2] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn vertex_normals<H: HeightValue>(&self, height_data: &[H], width: usize) -> Vec<[f32; 3]> {
+        compute_vertex_normals(&self.points, &self.triangles(), height_data, width)
+    }
+
+    /// Slope of every triangle in degrees, from 0 for flat to 90 for vertical, e.g. for
+    /// terrain analysis on the simplified surface instead of the grid.
+    ///
+    /// Slope is the angle between the normal of the triangle and the vertical, with heights
+    /// in the same units as the cell size. Degenerate triangles have zero slope.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `cell_size` - Ground size of a pixel in x and y.
+    ///
+    /// # Returns
+    ///
+    /// A vector of slopes in the same order as `triangles`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// // plane rising by 10 per pixel of 10 meters to the right
+    /// let heights = (0..25).map(|i| (i % 5) as f64 * 10.0).collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (5, 5), Error(0.0))?;
+    ///
+    /// for slope in mesh.slopes(&heights, 5, (10.0, 10.0)) {
+    ///     assert!((slope - 45.0).abs() < 1e-9);
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn slopes<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        cell_size: (f64, f64),
+    ) -> Vec<f64> {
+        self.iter_triangles()
+            .map(|triangle| {
+                let normal = self.face_normal(triangle, height_data, width, cell_size);
+                let horizontal = (normal[0] * normal[0] + normal[1] * normal[1]).sqrt();
+                horizontal.atan2(normal[2]).to_degrees()
+            })
+            .collect()
+    }
+
+    /// Aspect of every triangle, compass direction of the steepest descent in degrees
+    /// clockwise from north, which is up the grid (decreasing y).
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `cell_size` - Ground size of a pixel in x and y.
+    ///
+    /// # Returns
+    ///
+    /// A vector of aspects in `[0, 360)` in the same order as `triangles`, `None` for flat
+    /// and degenerate triangles.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// // plane rising to the right, so it faces west
+    /// let heights = (0..25).map(|i| (i % 5) as f64).collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (5, 5), Error(0.0))?;
+    ///
+    /// for aspect in mesh.aspects(&heights, 5, (1.0, 1.0)) {
+    ///     assert_eq!(aspect, Some(270.0));
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn aspects<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        cell_size: (f64, f64),
+    ) -> Vec<Option<f64>> {
+        self.iter_triangles()
+            .map(|triangle| {
+                let normal = self.face_normal(triangle, height_data, width, cell_size);
+                if normal[0] == 0.0 && normal[1] == 0.0 {
+                    return None;
+                }
+
+                // horizontal part of the upward normal points downhill, north is negative y
+                let aspect = normal[0].atan2(-normal[1]).to_degrees();
+                Some(if aspect < 0.0 { aspect + 360.0 } else { aspect })
+            })
+            .collect()
+    }
+
+    /// Normal of the triangle in world units pointing up (positive z), not normalized.
+    fn face_normal<H: HeightValue>(
+        &self,
+        (a, b, c): Triangle,
+        height_data: &[H],
+        width: usize,
+        cell_size: (f64, f64),
+    ) -> [f64; 3] {
+        let [point_a, point_b, point_c] = [a, b, c].map(|vertex| {
+            let point = self.points[vertex];
+            [
+                point.0 as f64 * cell_size.0,
+                point.1 as f64 * cell_size.1,
+                height_data[point.1 * width + point.0].to_f64(),
+            ]
+        });
+        let ab = [0, 1, 2].map(|i| point_b[i] - point_a[i]);
+        let ac = [0, 1, 2].map(|i| point_c[i] - point_a[i]);
+        let normal = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+
+        if normal[2] < 0.0 {
+            normal.map(|value| -value)
+        } else {
+            normal
+        }
+    }
+
+    /// Height of the simplified surface at the point, which doesn't have to be a grid vertex.
+    ///
+    /// Finds the triangle containing the point by a linear scan, so it takes `O(n)` time
+    /// for `n` triangles, and interpolates heights of its vertices by barycentric weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `x` - X coordinate of the point.
+    /// * `y` - Y coordinate of the point.
+    ///
+    /// # Returns
+    ///
+    /// Interpolated height, or `None` if the point is not covered by any triangle.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// // plane z = x + 2y
+    /// let heights = (0..25).map(|i| (i % 5) as f64 + 2.0 * (i / 5) as f64).collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (5, 5), Error(0.0))?;
+    ///
+    /// assert!((mesh.sample(&heights, 5, 1.5, 2.25).unwrap() - 6.0).abs() < 1e-9);
+    /// assert_eq!(mesh.sample(&heights, 5, 4.0, 4.0), Some(12.0));
+    /// assert_eq!(mesh.sample(&heights, 5, 4.5, 1.0), None);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn sample<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        x: f64,
+        y: f64,
+    ) -> Option<f64> {
+        // tolerance for points on edges shared by triangles
+        const TOLERANCE: f64 = 1e-12;
+
+        self.triangles.chunks_exact(3).find_map(|triangle| {
+            let [point_a, point_b, point_c] = [0, 1, 2].map(|i| self.points[triangle[i]]);
+            let (weight_a, weight_b, weight_c) =
+                barycentric_weights(point_a, point_b, point_c, (x, y))?;
+            if weight_a < -TOLERANCE || weight_b < -TOLERANCE || weight_c < -TOLERANCE {
+                return None;
+            }
+
+            let height_at = |point: Point| height_data[point.1 * width + point.0].to_f64();
+            Some(
+                height_at(point_a) * weight_a
+                    + height_at(point_b) * weight_b
+                    + height_at(point_c) * weight_c,
+            )
+        })
+    }
+
+    /// Circumcenters of all triangles, which are vertices of the dual (Voronoi) diagram.
+    ///
+    /// # Returns
+    ///
+    /// Circumcenter of each triangle in the order of triangles, `None` for degenerate triangles.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = vec![100.0; 9];
+    /// let mesh = triangulate_mesh(&heights, (3, 3), Error(1.0))?;
+    ///
+    /// // both initial triangles share the circumcircle of the grid corners
+    /// assert_eq!(mesh.circumcenters(), vec![Some((1.0, 1.0)), Some((1.0, 1.0))]);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn circumcenters(&self) -> Vec<Option<(f64, f64)>> {
+        self.triangles
+            .chunks_exact(3)
+            .map(|triangle| {
+                circumcircle(
+                    self.points[triangle[0]],
+                    self.points[triangle[1]],
+                    self.points[triangle[2]],
+                )
+                .map(|(center, _)| center)
+            })
+            .collect()
+    }
+
+    /// Convex hull of the vertices, e.g. for clipping and bounds.
+    ///
+    /// Computed with Andrew's monotone chain, so points lying on hull edges are left out.
+    ///
+    /// # Returns
+    ///
+    /// Hull points starting from the one with the smallest x (and smallest y among those),
+    /// in the same winding as mesh triangles. Empty for a mesh without points.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error, Mesh};
+    ///
+    /// let heights = (0..25).map(|i| ((i * 7) % 5) as f64).collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (5, 5), Error(0.0))?;
+    /// assert_eq!(mesh.convex_hull(), vec![(0, 0), (0, 4), (4, 4), (4, 0)]);
+    ///
+    /// let scattered = vec![(2, 0), (1, 1), (4, 2), (2, 2), (0, 3), (3, 3), (2, 4), (1, 3)];
+    /// let mesh = Mesh::from_triangles(scattered, &[]);
+    /// assert_eq!(mesh.convex_hull(), vec![(0, 3), (2, 4), (4, 2), (2, 0), (1, 1)]);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn convex_hull(&self) -> Vec<Point> {
+        let mut points = self.points.clone();
+        points.sort_unstable();
+        points.dedup();
+        if points.len() < 3 {
+            return points;
+        }
+
+        // builds one chain keeping only turns with the same winding as mesh triangles
+        let chain = |points: &mut dyn Iterator<Item = Point>| {
+            let mut chain: Vec<Point> = Vec::new();
+            for point in points {
+                while chain.len() >= 2
+                    && get_signed_area(chain[chain.len() - 2], chain[chain.len() - 1], point) <= 0
+                {
+                    chain.pop();
+                }
+                chain.push(point);
+            }
+            chain.pop();
+
+            chain
+        };
+
+        let mut hull = chain(&mut points.iter().copied());
+        hull.extend(chain(&mut points.iter().rev().copied()));
+
+        hull
+    }
+
+    /// Triangles touching the boundary of the mesh, e.g. for blending between levels of detail.
+    ///
+    /// # Returns
+    ///
+    /// Indices of triangles which have a boundary edge (edge without adjacent triangle)
+    /// or at least one vertex on the boundary.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = (0..25)
+    ///     .map(|i| ((i % 5) as f64 - 2.0).powi(2) + ((i / 5) as f64 - 2.0).powi(2))
+    ///     .collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (5, 5), Error(0.0))?;
+    /// let triangles = mesh.triangles();
+    /// let points = mesh.points();
+    ///
+    /// let ring = mesh.boundary_ring();
+    /// assert!(!ring.is_empty() && ring.len() < triangles.len());
+    /// for triangle_index in ring {
+    ///     let (a, b, c) = triangles[triangle_index];
+    ///     assert!([a, b, c].iter().any(|&vertex| {
+    ///         let (x, y) = points[vertex];
+    ///         x == 0 || y == 0 || x == 4 || y == 4
+    ///     }));
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn boundary_ring(&self) -> Vec<usize> {
+        let mut is_boundary_vertex = vec![false; self.points.len()];
+        for (edge, half_edge) in self.half_edges.iter().enumerate() {
+            if half_edge.is_none() {
+                let next_edge = next_half_edge(edge);
+                is_boundary_vertex[self.triangles[edge]] = true;
+                is_boundary_vertex[self.triangles[next_edge]] = true;
+            }
+        }
+
+        self.triangles
+            .chunks_exact(3)
+            .enumerate()
+            .filter(|(_, triangle)| triangle.iter().any(|&vertex| is_boundary_vertex[vertex]))
+            .map(|(triangle_index, _)| triangle_index)
+            .collect()
+    }
+
+    /// Adds a skirt of vertical triangles around the mesh, hiding cracks between tiles
+    /// triangulated independently.
+    ///
+    /// Boundary edges are walked in loops through the half-edges without adjacent triangle.
+    /// Every boundary vertex gets a copy dropped to `depth` below the lowest vertex of the mesh,
+    /// and every boundary edge gets a quad of two triangles down to these copies. Skirt triangles
+    /// have the same winding as mesh triangles, so together they are consistently oriented.
+    ///
+    /// # Arguments
+    ///
+    /// * `depth` - How far below the lowest vertex the skirt goes.
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    ///
+    /// # Returns
+    ///
+    /// Tuple of vertices as x, y and height and triangles. Vertices start with points of
+    /// the mesh in the same order with their heights, followed by skirt vertices.
+    /// Triangles start with triangles of the mesh, followed by skirt triangles.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = (0..25)
+    ///     .map(|i| ((i % 5) as f64 - 2.0).powi(2) + ((i / 5) as f64 - 2.0).powi(2))
+    ///     .collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (5, 5), Error(0.5))?;
+    ///
+    /// let (vertices, triangles) = mesh.add_skirt(10.0, &heights, 5);
+    /// let skirt_vertices = &vertices[mesh.points().len()..];
+    /// assert!(skirt_vertices.iter().all(|vertex| vertex.2 == -10.0));
+    /// assert_eq!(triangles.len(), mesh.triangles().len() + 2 * skirt_vertices.len());
+    ///
+    /// // every edge is shared by two triangles, except the bottom edges of the skirt
+    /// let mut edges = HashMap::new();
+    /// for &(a, b, c) in &triangles {
+    ///     for (start, end) in [(a, b), (b, c), (c, a)] {
+    ///         *edges.entry((start.min(end), start.max(end))).or_insert(0) += 1;
+    ///     }
+    /// }
+    /// for ((start, end), count) in edges {
+    ///     let is_bottom = vertices[start].2 == -10.0 && vertices[end].2 == -10.0;
+    ///     assert_eq!(count, if is_bottom { 1 } else { 2 });
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn add_skirt<H: HeightValue>(
+        &self,
+        depth: f64,
+        height_data: &[H],
+        width: usize,
+    ) -> (Vec<PointWithHeight>, Vec<Triangle>) {
+        let mut vertices = self
+            .points
+            .iter()
+            .map(|point| {
+                (
+                    point.0,
+                    point.1,
+                    height_data[point.1 * width + point.0].to_f64(),
+                )
+            })
+            .collect::<Vec<PointWithHeight>>();
+        let mut triangles = self.triangles();
+        let bottom = vertices
+            .iter()
+            .map(|vertex| vertex.2)
+            .fold(f64::INFINITY, f64::min)
+            - depth;
+
+        for boundary_loop in self.boundary_loops() {
+            let first_bottom_vertex = vertices.len();
+            for &edge in &boundary_loop {
+                let point = self.points[self.triangles[edge]];
+                vertices.push((point.0, point.1, bottom));
+            }
+
+            for position in 0..boundary_loop.len() {
+                let next_position = (position + 1) % boundary_loop.len();
+                let start = self.triangles[boundary_loop[position]];
+                let end = self.triangles[boundary_loop[next_position]];
+                let start_bottom = first_bottom_vertex + position;
+                let end_bottom = first_bottom_vertex + next_position;
+
+                // the boundary edge goes from start to end, skirt triangles go the other way
+                triangles.push((end, start, start_bottom));
+                triangles.push((end, start_bottom, end_bottom));
+            }
+        }
+
+        (vertices, triangles)
+    }
+
+    /// Boundary half-edges of the mesh, grouped in closed loops.
+    ///
+    /// Half-edges in a loop are ordered, so every one ends at the vertex where the next one
+    /// starts. The next boundary half-edge is found by rotating around the shared vertex
+    /// through adjacent triangles until an edge without adjacent triangle is reached.
+    pub(crate) fn boundary_loops(&self) -> Vec<Vec<usize>> {
+        let mut is_visited = vec![false; self.half_edges.len()];
+        let mut loops = Vec::new();
+
+        for start_edge in 0..self.half_edges.len() {
+            if self.half_edges[start_edge].is_some() || is_visited[start_edge] {
+                continue;
+            }
+
+            let mut boundary_loop = Vec::new();
+            let mut edge = start_edge;
+            while !is_visited[edge] {
+                is_visited[edge] = true;
+                boundary_loop.push(edge);
+
+                // rotate around the end vertex, bounded in case of non-manifold meshes
+                edge = next_half_edge(edge);
+                for _ in 0..self.half_edges.len() {
+                    match self.half_edges[edge] {
+                        Some(opposite) => edge = next_half_edge(opposite),
+                        None => break,
+                    }
+                }
+            }
+            loops.push(boundary_loop);
+        }
+
+        loops
+    }
+
+    /// Counts triangles by their area, e.g. to estimate rendering cost.
+    ///
+    /// # Arguments
+    ///
+    /// * `thresholds` - Ascending area thresholds in square pixels.
+    ///
+    /// # Returns
+    ///
+    /// Vector of `thresholds.len() + 1` counts. Bucket `i` counts triangles with area (rounded down)
+    /// below `thresholds[i]` and not below `thresholds[i - 1]`, the last bucket counts the rest.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error};
+    ///
+    /// let heights = (0..81)
+    ///     .map(|i| ((i % 9) as f64 - 4.0).powi(2) + ((i / 9) as f64 - 4.0).powi(2))
+    ///     .collect::<Vec<f64>>();
+    /// let mesh = triangulate_mesh(&heights, (9, 9), Error(0.5))?;
+    ///
+    /// let size_classes = mesh.size_classes(&[2, 8]);
+    /// assert_eq!(size_classes.len(), 3);
+    /// assert_eq!(size_classes.iter().sum::<usize>(), mesh.triangles().len());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn size_classes(&self, thresholds: &[usize]) -> Vec<usize> {
+        let mut size_classes = vec![0; thresholds.len() + 1];
+        for triangle in self.triangles.chunks_exact(3) {
+            let signed_area = get_signed_area(
+                self.points[triangle[0]],
+                self.points[triangle[1]],
+                self.points[triangle[2]],
+            );
+            let area = (signed_area.unsigned_abs() / 2) as usize;
+
+            size_classes[thresholds.partition_point(|&threshold| threshold <= area)] += 1;
+        }
+
+        size_classes
+    }
+
+    /// Error of every pixel of the grid, the absolute difference between the mesh surface
+    /// and the grid, e.g. for a heatmap of where the mesh approximates the surface poorly.
+    ///
+    /// Every triangle is rasterized like during refinement, so it is as heavy as one more
+    /// pass over the whole grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `height` - The height of the grid.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `width * height` errors in the order of `height_data`, `NaN` for pixels
+    /// not covered by any triangle.
+    pub fn error_map<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        height: usize,
+    ) -> Vec<f64> {
+        let mut errors = vec![f64::NAN; width * height];
+        for triangle in self.triangles.chunks_exact(3) {
+            let [point_a, point_b, point_c] = [0, 1, 2].map(|i| self.points[triangle[i]]);
+            let [height_a, height_b, height_c] = [point_a, point_b, point_c]
+                .map(|point| height_data[point.1 * width + point.0].to_f64());
+
+            for_each_triangle_pixel(point_a, point_b, point_c, |point, weights| {
+                let z = height_a * weights.0 + height_b * weights.1 + height_c * weights.2;
+                let index = point.1 * width + point.0;
+                errors[index] = (z - height_data[index].to_f64()).abs();
+            });
+        }
+
+        errors
+    }
+
+    /// Checks that triangles of the mesh don't overlap, e.g. to detect broken output instead
+    /// of shipping it.
+    ///
+    /// A triangle is reported if its signed area is not positive (reversed or degenerate),
+    /// if a vertex index is out of range, if another triangle has the same directed edge
+    /// (so they overlap), or if its half-edges don't point to a twin which points back
+    /// along the same edge.
+    ///
+    /// # Errors
+    ///
+    /// Sorted indices of offending triangles, the same as in [`Mesh::triangles`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error, Mesh};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 4.0).sin() * ((i / 33) as f64 / 6.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    /// assert_eq!(triangulate_mesh(&heights, (33, 33), Error(1.0))?.validate(), Ok(()));
+    ///
+    /// // the last triangle is reversed and covers the first one, it has the same directed
+    /// // edge from (2, 2) to (0, 0) as the second one
+    /// let points = vec![(0, 0), (2, 0), (2, 2), (0, 2)];
+    /// let mesh = Mesh::from_triangles(points, &[(0, 2, 1), (0, 3, 2), (0, 1, 2)]);
+    /// assert_eq!(mesh.validate(), Err(vec![1, 2]));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<usize>> {
+        let triangle_count = self.triangles.len() / 3;
+        let mut is_offending = vec![false; triangle_count];
+        let mut edge_triangles = HashMap::with_capacity(self.triangles.len());
+
+        for triangle_index in 0..triangle_count {
+            let triangle = &self.triangles[3 * triangle_index..3 * triangle_index + 3];
+            if triangle.iter().any(|&vertex| vertex >= self.points.len()) {
+                is_offending[triangle_index] = true;
+                continue;
+            }
+
+            let [a, b, c] = [0, 1, 2].map(|i| self.points[triangle[i]]);
+            if get_signed_area(a, b, c) <= 0 {
+                is_offending[triangle_index] = true;
+            }
+            for edge in 0..3 {
+                let key = (triangle[edge], triangle[(edge + 1) % 3]);
+                if let Some(other_index) = edge_triangles.insert(key, triangle_index) {
+                    is_offending[other_index] = true;
+                    is_offending[triangle_index] = true;
+                }
+            }
+        }
+
+        for (edge, &twin) in self.half_edges.iter().enumerate() {
+            let Some(twin) = twin else {
+                continue;
+            };
+
+            let is_consistent = self.half_edges.get(twin) == Some(&Some(edge))
+                && self.triangles[edge] == self.triangles[next_half_edge(twin)]
+                && self.triangles[next_half_edge(edge)] == self.triangles[twin];
+            if !is_consistent {
+                is_offending[edge / 3] = true;
+            }
+        }
+
+        let offending = (0..triangle_count)
+            .filter(|&triangle_index| is_offending[triangle_index])
+            .collect::<Vec<usize>>();
+        if offending.is_empty() {
+            Ok(())
+        } else {
+            Err(offending)
+        }
+    }
+
+    /// Checks that the max error reported by the triangulation matches the real one.
+    ///
+    /// Every triangle is rasterized again and the largest difference between the mesh
+    /// surface and the grid is compared with [`Mesh::max_error`]. A mismatch means the
+    /// errors tracked during refinement diverged from the mesh, so it's a debugging aid for
+    /// bugs in triangle updates. Errors weighted by confidence are not plain differences,
+    /// so they don't match.
+    ///
+    /// The mesh has to pass [`Mesh::validate`] first, as the error doesn't reveal broken
+    /// topology, e.g. a triangle missing next to the worst pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    ///
+    /// # Returns
+    ///
+    /// Whether the mesh is valid and its max error matches, `false` as well when the height
+    /// data doesn't split into rows of `width` or points lie outside of the grid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error, Mesh};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 4.0).sin() * ((i / 33) as f64 / 6.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// for max_error in [0.1, 1.0, 10.0] {
+    ///     let mesh = triangulate_mesh(&heights, (33, 33), Error(max_error))?;
+    ///     assert!(mesh.verify_consistency(&heights, 33));
+    /// }
+    ///
+    /// let mesh = triangulate_mesh(&heights, (33, 33), Error(1.0))?;
+    /// assert!(!mesh.verify_consistency(&heights, 32));
+    /// assert!(!mesh.verify_consistency(&heights[..33 * 20], 33));
+    ///
+    /// // the same triangles twice overlap
+    /// let mut triangles = mesh.triangles();
+    /// triangles.extend(mesh.triangles());
+    /// let overlapping = Mesh::from_triangles(mesh.points().to_vec(), &triangles);
+    /// assert!(!overlapping.verify_consistency(&heights, 33));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn verify_consistency<H: HeightValue>(&self, height_data: &[H], width: usize) -> bool {
+        const TOLERANCE: f64 = 1e-9;
+
+        let fits_grid = width > 0
+            && height_data.len() % width == 0
+            && self
+                .points
+                .iter()
+                .all(|point| point.0 < width && point.1 < height_data.len() / width);
+        if !fits_grid || self.validate().is_err() {
+            return false;
+        }
+
+        let max_error = self.measure_max_error(height_data, width);
+        (max_error - self.max_error.0).abs() <= TOLERANCE * max_error.max(1.0)
+    }
+
+    /// Largest difference between the mesh surface and the grid over all covered pixels.
+    fn measure_max_error<H: HeightValue>(&self, height_data: &[H], width: usize) -> f64 {
+        let mut max_error: f64 = 0.0;
+        for triangle in self.triangles.chunks_exact(3) {
+            let point_a = self.points[triangle[0]];
+            let point_b = self.points[triangle[1]];
+            let point_c = self.points[triangle[2]];
+            let height_a = height_data[point_a.1 * width + point_a.0].to_f64();
+            let height_b = height_data[point_b.1 * width + point_b.0].to_f64();
+            let height_c = height_data[point_c.1 * width + point_c.0].to_f64();
+
+            for_each_triangle_pixel(point_a, point_b, point_c, |point, weights| {
+                let z = height_a * weights.0 + height_b * weights.1 + height_c * weights.2;
+                max_error =
+                    max_error.max((z - height_data[point.1 * width + point.0].to_f64()).abs());
+            });
+        }
+
+        max_error
+    }
+
+    /// Removes zero-area triangles keeping the mesh watertight, for tools rejecting degenerate faces.
+    ///
+    /// Vertex lying on the longest edge of a degenerate triangle is connected to the opposite
+    /// vertex of the neighbouring triangle across that edge, which splits the neighbour in two
+    /// and lets the degenerate triangle be removed without leaving a crack. When the neighbour
+    /// is degenerate too or was already split in the same pass, the triangle waits for the next
+    /// pass. Collapse is impossible and the degenerate triangle is just dropped when the longest
+    /// edge is on the mesh boundary (the outline doesn't change as the vertex lies on it), when
+    /// two of its vertices coincide, or when degenerate neighbours only wait for each other,
+    /// which happens when they all lie on one segment.
+    ///
+    /// # Returns
+    ///
+    /// A new mesh without zero-area triangles, with the same points and max error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    ///
+    /// use delatin::Mesh;
+    ///
+    /// // square with a sliver triangle along its bottom edge
+    /// let points = vec![(0, 0), (1, 0), (2, 0), (2, 2), (0, 2)];
+    /// let mesh = Mesh::from_triangles(points.clone(), &[(0, 3, 2), (0, 4, 3), (0, 2, 1)]);
+    ///
+    /// let collapsed = mesh.collapse_degenerate();
+    /// let triangles = collapsed.triangles();
+    /// assert_eq!(triangles.len(), 3);
+    ///
+    /// // points on the same side of the square
+    /// let on_outline = |p: (usize, usize), q: (usize, usize)| {
+    ///     (p.0 == q.0 && p.0 % 2 == 0) || (p.1 == q.1 && p.1 % 2 == 0)
+    /// };
+    /// let mut edges = HashMap::new();
+    /// for (a, b, c) in triangles {
+    ///     let [a, b, c] = [a, b, c].map(|vertex| points[vertex]);
+    ///     assert!(!(on_outline(a, b) && on_outline(b, c) && on_outline(c, a)));
+    ///     for (start, end) in [(a, b), (b, c), (c, a)] {
+    ///         *edges.entry((start.min(end), start.max(end))).or_insert(0) += 1;
+    ///     }
+    /// }
+    /// // edges used by a single triangle are only on the outline, so there are no cracks
+    /// for ((start, end), count) in edges {
+    ///     assert!(count == 2 || on_outline(start, end));
+    /// }
+    /// ```
+    pub fn collapse_degenerate(&self) -> Mesh {
+        let mut triangles = self.triangles.clone();
+        let is_degenerate = |triangles: &[usize], triangle_index: usize| {
+            let [a, b, c] = [0, 1, 2].map(|i| self.points[triangles[3 * triangle_index + i]]);
+            get_signed_area(a, b, c) == 0
+        };
+        let squared_length = |start: Point, end: Point| {
+            start.0.abs_diff(end.0).pow(2) + start.1.abs_diff(end.1).pow(2)
+        };
+
+        // set after a pass where every degenerate triangle waited for its neighbour
+        let mut is_stuck = false;
+        loop {
+            let half_edges = find_half_edges(&triangles);
+            let triangle_count = triangles.len() / 3;
+            let mut dropped = vec![false; triangle_count];
+            let mut changed = vec![false; triangle_count];
+            let mut has_degenerate = false;
+            let mut has_waiting = false;
+
+            for triangle_index in 0..triangle_count {
+                if changed[triangle_index] || !is_degenerate(&triangles, triangle_index) {
+                    continue;
+                }
+                has_degenerate = true;
+
+                // longest edge goes from `a` to `b`, so `c` lies between them
+                let edge = (3 * triangle_index..3 * triangle_index + 3)
+                    .max_by_key(|&edge| {
+                        squared_length(
+                            self.points[triangles[edge]],
+                            self.points[triangles[next_half_edge(edge)]],
+                        )
+                    })
+                    .expect("Triangle has three edges.");
+                let a = triangles[edge];
+                let b = triangles[next_half_edge(edge)];
+                let c = triangles[previous_half_edge(edge)];
+
+                let has_coincident_vertices = [(a, b), (b, c), (c, a)]
+                    .iter()
+                    .any(|&(start, end)| self.points[start] == self.points[end]);
+                let neighbour = half_edges[edge].filter(|_| !has_coincident_vertices);
+                let is_waiting = neighbour
+                    .is_some_and(|twin| changed[twin / 3] || is_degenerate(&triangles, twin / 3));
+                if is_waiting && !is_stuck {
+                    has_waiting = true;
+                    continue;
+                }
+
+                changed[triangle_index] = true;
+                let Some(twin) = neighbour.filter(|_| !is_waiting) else {
+                    dropped[triangle_index] = true;
+                    continue;
+                };
+
+                // split neighbour `b, a, d` at `c` reusing the degenerate triangle
+                let d = triangles[previous_half_edge(twin)];
+                let neighbour_index = twin / 3;
+                triangles[3 * triangle_index..3 * triangle_index + 3].copy_from_slice(&[b, c, d]);
+                triangles[3 * neighbour_index..3 * neighbour_index + 3].copy_from_slice(&[c, a, d]);
+                changed[neighbour_index] = true;
+            }
+
+            if !has_degenerate {
+                break;
+            }
+            is_stuck = has_waiting && !changed.contains(&true);
+
+            triangles = triangles
+                .chunks_exact(3)
+                .zip(dropped)
+                .filter(|(_, dropped)| !dropped)
+                .flat_map(|(triangle, _)| triangle.to_vec())
+                .collect();
+        }
+
+        let half_edges = find_half_edges(&triangles);
+        Self::new(self.points.clone(), triangles, half_edges, self.max_error)
+    }
+
+    /// Drops zero-area triangles, e.g. before computing face normals.
+    ///
+    /// Triangulation doesn't create them, but meshes edited or made by other tools may have them.
+    ///
+    /// Unlike [`Mesh::collapse_degenerate`] neighbours of dropped triangles are not split,
+    /// so a vertex lying on an edge of a neighbour leaves a crack where the degenerate
+    /// triangle was. Points are kept, so indices of vertices don't change.
+    ///
+    /// # Returns
+    ///
+    /// A new mesh without zero-area triangles, with the same points and max error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::Mesh;
+    ///
+    /// // square with a sliver triangle along its bottom edge
+    /// let points = vec![(0, 0), (1, 0), (2, 0), (2, 2), (0, 2)];
+    /// let mesh = Mesh::from_triangles(points, &[(0, 3, 2), (0, 4, 3), (0, 2, 1)]);
+    ///
+    /// let cleaned = mesh.remove_degenerate();
+    /// assert_eq!(cleaned.triangles(), vec![(0, 3, 2), (0, 4, 3)]);
+    /// assert_eq!(cleaned.points(), mesh.points());
+    /// ```
+    pub fn remove_degenerate(&self) -> Mesh {
+        let triangles = self
+            .triangles
+            .chunks_exact(3)
+            .filter(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| self.points[triangle[i]]);
+                get_signed_area(a, b, c) != 0
+            })
+            .flatten()
+            .copied()
+            .collect::<Vec<usize>>();
+        let half_edges = find_half_edges(&triangles);
+
+        Mesh::new(self.points.clone(), triangles, half_edges, self.max_error)
+    }
+
+    /// Reverses triangles wound the other way than the ones made by triangulation, e.g. in
+    /// meshes built with [`Mesh::from_triangles`] from other tools.
+    ///
+    /// Triangulation winds triangles counterclockwise when drawn with y growing downwards like
+    /// rows of the grid, so [`signed_area`](crate::predicates::signed_area) of each is positive.
+    /// Triangles with negative area are reversed from `(a, b, c)` to `(a, c, b)`, zero-area
+    /// triangles are kept as they are. Points are kept, so indices of vertices don't change.
+    ///
+    /// # Returns
+    ///
+    /// A new mesh with all non-degenerate triangles of positive signed area, with the same
+    /// points and max error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::predicates::signed_area;
+    /// use delatin::Mesh;
+    ///
+    /// // square split by its diagonal, with the second half wound clockwise
+    /// let points = vec![(0, 0), (2, 0), (2, 2), (0, 2)];
+    /// let mesh = Mesh::from_triangles(points, &[(0, 2, 1), (0, 2, 3)]);
+    ///
+    /// let ccw = mesh.ensure_ccw();
+    /// assert_eq!(ccw.triangles(), vec![(0, 2, 1), (0, 3, 2)]);
+    /// assert_eq!(ccw.validate(), Ok(()));
+    /// for (a, b, c) in ccw.iter_triangles() {
+    ///     let [a, b, c] = [a, b, c].map(|vertex| ccw.points()[vertex]);
+    ///     assert!(signed_area(a, b, c) > 0);
+    /// }
+    /// ```
+    pub fn ensure_ccw(&self) -> Mesh {
+        let triangles = self
+            .triangles
+            .chunks_exact(3)
+            .flat_map(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| self.points[triangle[i]]);
+                if get_signed_area(a, b, c) < 0 {
+                    [triangle[0], triangle[2], triangle[1]]
+                } else {
+                    [triangle[0], triangle[1], triangle[2]]
+                }
+            })
+            .collect::<Vec<usize>>();
+        let half_edges = find_half_edges(&triangles);
+
+        Mesh::new(self.points.clone(), triangles, half_edges, self.max_error)
+    }
+
+    /// Drops triangles over voids, e.g. after triangulating with
+    /// [`TriangulationOptions::nodata`](crate::TriangulationOptions::nodata).
+    ///
+    /// Triangles with a vertex on a void pixel are dropped, so valid pixels inside them are
+    /// lost unless refinement covered them with triangles of valid vertices, like
+    /// [`triangulate_regions`](crate::triangulate_regions) does. Triangles with valid vertices
+    /// are dropped when all pixels strictly inside them are void, e.g. ones bridging two
+    /// separate regions. The rest covers valid pixels, with holes and separate parts where
+    /// voids were. Points are kept, so indices of vertices don't change.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from, of any [`HeightValue`] type.
+    /// * `stride` - The number of values per row of the data, the width of the grid unless the
+    ///   mesh covers a region of a wider grid with the data starting at its top-left pixel.
+    /// * `nodata` - Sentinel height of void pixels, `NaN` sentinel matches any `NaN`.
+    ///
+    /// # Returns
+    ///
+    /// A new mesh without triangles over voids, with the same points and max error.
+    pub fn remove_nodata<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        stride: usize,
+        nodata: f64,
+    ) -> Mesh {
+        let is_void = |(x, y): Point| is_nodata_value(height_data[y * stride + x].to_f64(), nodata);
+        let triangles = self
+            .triangles
+            .chunks_exact(3)
+            .filter(|triangle| {
+                let [a, b, c] = [0, 1, 2].map(|i| self.points[triangle[i]]);
+                if [a, b, c].into_iter().any(is_void) {
+                    return false;
+                }
+
+                let mut has_interior = false;
+                let mut has_valid_interior = false;
+                for_each_triangle_pixel(a, b, c, |point, (weight_a, weight_b, weight_c)| {
+                    if weight_a > 0.0 && weight_b > 0.0 && weight_c > 0.0 {
+                        has_interior = true;
+                        has_valid_interior |= !is_void(point);
+                    }
+                });
+
+                !has_interior || has_valid_interior
+            })
+            .flatten()
+            .copied()
+            .collect::<Vec<usize>>();
+        let half_edges = find_half_edges(&triangles);
+
+        Mesh::new(self.points.clone(), triangles, half_edges, self.max_error)
+    }
+
+    /// Splits the mesh into parts connected through shared edges, e.g. separate islands
+    /// of a mesh after [`Mesh::remove_nodata`]. Triangles touching only at a vertex are in
+    /// different parts.
+    ///
+    /// # Returns
+    ///
+    /// A mesh for every part with the same max error, ordered by their first triangle.
+    /// Each part has only its own points, so indices of vertices change.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::Mesh;
+    ///
+    /// // two squares side by side, not sharing any vertex
+    /// let points = vec![(0, 0), (1, 0), (1, 1), (0, 1), (3, 0), (4, 0), (4, 1), (3, 1)];
+    /// let mesh = Mesh::from_triangles(points, &[(0, 2, 1), (4, 6, 5), (0, 3, 2), (4, 7, 6)]);
+    ///
+    /// let parts = mesh.components();
+    /// assert_eq!(parts.len(), 2);
+    /// assert_eq!(parts[1].points(), &[(3, 0), (4, 1), (4, 0), (3, 1)]);
+    /// assert_eq!(parts[1].triangles(), vec![(0, 1, 2), (0, 3, 1)]);
+    /// ```
+    pub fn components(&self) -> Vec<Mesh> {
+        let triangle_count = self.triangles.len() / 3;
+        let mut is_visited = vec![false; triangle_count];
+        let mut components = Vec::new();
+
+        for first_triangle_index in 0..triangle_count {
+            if is_visited[first_triangle_index] {
+                continue;
+            }
+
+            is_visited[first_triangle_index] = true;
+            let mut component = Vec::new();
+            let mut triangles_to_visit = vec![first_triangle_index];
+            while let Some(triangle_index) = triangles_to_visit.pop() {
+                component.push(triangle_index);
+                for neighbour in self.neighbors(triangle_index).into_iter().flatten() {
+                    if !is_visited[neighbour] {
+                        is_visited[neighbour] = true;
+                        triangles_to_visit.push(neighbour);
+                    }
+                }
+            }
+            component.sort_unstable();
+
+            // keep vertices in order of their first use
+            let mut vertex_indices = HashMap::new();
+            let mut points = Vec::new();
+            let mut triangles = Vec::with_capacity(3 * component.len());
+            for triangle_index in component {
+                for &vertex in &self.triangles[3 * triangle_index..3 * triangle_index + 3] {
+                    let index = *vertex_indices.entry(vertex).or_insert_with(|| {
+                        points.push(self.points[vertex]);
+                        points.len() - 1
+                    });
+                    triangles.push(index);
+                }
+            }
+            let half_edges = find_half_edges(&triangles);
+
+            components.push(Mesh::new(points, triangles, half_edges, self.max_error));
+        }
+
+        components
+    }
+
+    /// Merges coplanar triangles to reduce triangle count on flat areas.
+    ///
+    /// Repeatedly removes interior vertices whose neighbours all lie within `tolerance` of the
+    /// plane of one of the surrounding triangles, and triangulates the hole again by ear clipping.
+    /// Vertices on the boundary of the mesh are kept, so its outline doesn't change. Merged
+    /// regions are not triangulated in Delaunay way, so triangles may get thin.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `width` - The width of the grid.
+    /// * `tolerance` - Maximum height difference from the plane for vertices considered coplanar.
+    ///
+    /// # Returns
+    ///
+    /// A new mesh with max error measured again on the grid, since merging within
+    /// `tolerance` may move the surface away from the grid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, Mesh, TriangulationOptions};
+    ///
+    /// // plane densely triangulated because of the edge length limit
+    /// let heights = (0..17 * 17)
+    ///     .map(|i| (i % 17) as f64 * 0.5 + (i / 17) as f64 * 2.0)
+    ///     .collect::<Vec<f64>>();
+    /// let options = TriangulationOptions::new().max_edge_length(3.0);
+    /// let (points, triangles) = options.clone().max_error(Error(1.0)).run(&heights, 17, 17)?;
+    /// let mesh = Mesh::from_triangles(points, &triangles);
+    ///
+    /// let merged = mesh.merge_coplanar(&heights, 17, 1e-9);
+    /// assert!(merged.triangles().len() < mesh.triangles().len());
+    /// assert!(merged.verify_consistency(&heights, 17));
+    ///
+    /// // bumps within the tolerance are flattened, which raises the error
+    /// let bumpy = heights
+    ///     .iter()
+    ///     .enumerate()
+    ///     .map(|(i, height)| height + ((i * 7) % 5) as f64 * 0.1)
+    ///     .collect::<Vec<f64>>();
+    /// let mesh = options
+    ///     .max_error(Error(0.1))
+    ///     .run(&bumpy, 17, 17)
+    ///     .map(|(points, triangles)| Mesh::from_triangles(points, &triangles))?;
+    /// let merged = mesh.merge_coplanar(&bumpy, 17, 0.5);
+    /// assert!(merged.triangles().len() < mesh.triangles().len());
+    /// assert!(merged.max_error().0 > 0.1);
+    /// assert!(merged.verify_consistency(&bumpy, 17));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn merge_coplanar<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        tolerance: f64,
+    ) -> Mesh {
+        let height_at = |point: Point| height_data[point.1 * width + point.0].to_f64();
+        let mut triangles = self.triangles.clone();
+        let mut half_edges = self.half_edges.clone();
+
+        loop {
+            // any outgoing half edge of each vertex
+            let mut vertex_edges = vec![None; self.points.len()];
+            for (edge, &vertex) in triangles.iter().enumerate() {
+                vertex_edges[vertex] = Some(edge);
+            }
+
+            // vertices next to a removed one, their stars changed during this pass
+            let mut is_locked = vec![false; self.points.len()];
+            let mut is_triangle_removed = vec![false; triangles.len() / 3];
+            let mut merged_triangles = Vec::new();
+
+            for vertex in 0..self.points.len() {
+                let Some(start_edge) = vertex_edges[vertex] else {
+                    continue;
+                };
+                if is_locked[vertex] {
+                    continue;
+                }
+
+                // walk around the vertex, stopping on the boundary
+                let mut ring = Vec::new();
+                let mut star = Vec::new();
+                let mut edge = start_edge;
+                let is_interior = loop {
+                    ring.push(triangles[next_half_edge(edge)]);
+                    star.push(edge / 3);
+                    let Some(twin) = half_edges[previous_half_edge(edge)] else {
+                        break false;
+                    };
+                    edge = twin;
+                    if edge == start_edge {
+                        break true;
+                    }
+                };
+                if !is_interior {
+                    continue;
+                }
+
+                let point = self.points[vertex];
+                let is_coplanar = ring.windows(2).find_map(|pair| {
+                    let point_a = self.points[pair[0]];
+                    let point_b = self.points[pair[1]];
+                    (get_signed_area(point, point_a, point_b) != 0).then(|| {
+                        ring.iter().all(|&ring_vertex| {
+                            let ring_point = self.points[ring_vertex];
+                            let plane_height = interpolate_height(
+                                (point, height_at(point)),
+                                (point_a, height_at(point_a)),
+                                (point_b, height_at(point_b)),
+                                ring_point,
+                            );
+                            (plane_height - height_at(ring_point)).abs() <= tolerance
+                        })
+                    })
+                });
+                if is_coplanar != Some(true) {
+                    continue;
+                }
+
+                let polygon = ring
+                    .iter()
+                    .map(|&ring_vertex| (ring_vertex, self.points[ring_vertex]))
+                    .collect::<Vec<(usize, Point)>>();
+                let Some(hole_triangles) = clip_ears(polygon) else {
+                    continue;
+                };
+
+                for triangle_index in star {
+                    is_triangle_removed[triangle_index] = true;
+                }
+                is_locked[vertex] = true;
+                for ring_vertex in ring {
+                    is_locked[ring_vertex] = true;
+                }
+                merged_triangles.extend(hole_triangles.into_iter().flat_map(|(a, b, c)| [a, b, c]));
+            }
+
+            if merged_triangles.is_empty() {
+                break;
+            }
+
+            triangles = triangles
+                .chunks_exact(3)
+                .enumerate()
+                .filter(|(triangle_index, _)| !is_triangle_removed[*triangle_index])
+                .flat_map(|(_, triangle)| triangle.iter().copied())
+                .chain(merged_triangles)
+                .collect();
+            half_edges = find_half_edges(&triangles);
+        }
+
+        // drop removed vertices
+        let mut new_vertex_indices = vec![None; self.points.len()];
+        let mut points = Vec::new();
+        for vertex in triangles.iter_mut() {
+            *vertex = *new_vertex_indices[*vertex].get_or_insert_with(|| {
+                points.push(self.points[*vertex]);
+                points.len() - 1
+            });
+        }
+
+        let mut merged = Mesh::new(points, triangles, half_edges, self.max_error);
+        merged.max_error = Error(merged.measure_max_error(height_data, width));
+
+        merged
+    }
+}
+
+fn next_half_edge(edge: usize) -> usize {
+    edge - edge % 3 + (edge + 1) % 3
+}
+
+fn previous_half_edge(edge: usize) -> usize {
+    edge - edge % 3 + (edge + 2) % 3
+}
+
+/// Finds adjacent half edge of each half edge by matching edges with opposite direction.
+fn find_half_edges(triangles: &[usize]) -> Vec<Option<usize>> {
+    let edges = (0..triangles.len())
+        .map(|edge| ((triangles[edge], triangles[next_half_edge(edge)]), edge))
+        .collect::<HashMap<(usize, usize), usize>>();
+
+    (0..triangles.len())
+        .map(|edge| {
+            edges
+                .get(&(triangles[next_half_edge(edge)], triangles[edge]))
+                .copied()
+        })
+        .collect()
+}
+
+/// Height at the point on the plane going through three vertices with their heights.
+fn interpolate_height(
+    vertex_a: (Point, Height),
+    vertex_b: (Point, Height),
+    vertex_c: (Point, Height),
+    point: Point,
+) -> Height {
+    let (weight_a, weight_b, weight_c) = barycentric_weights(
+        vertex_a.0,
+        vertex_b.0,
+        vertex_c.0,
+        (point.0 as f64, point.1 as f64),
+    )
+    .unwrap_or((f64::NAN, f64::NAN, f64::NAN));
+
+    vertex_a.1 * weight_a + vertex_b.1 * weight_b + vertex_c.1 * weight_c
+}
+
+/// Triangulates simple polygon by ear clipping. Triangles have positive signed area like
+/// triangles of the mesh. Returns `None` if polygon can't be triangulated without degenerate triangles.
+fn clip_ears(mut polygon: Vec<(usize, Point)>) -> Option<Vec<Triangle>> {
+    let orientation = (1..polygon.len() - 1)
+        .map(|i| get_signed_area(polygon[0].1, polygon[i].1, polygon[i + 1].1))
+        .sum::<i64>()
+        .signum();
+
+    let mut triangles = Vec::new();
+    while polygon.len() >= 3 {
+        let length = polygon.len();
+        let ear = (0..length).find(|&i| {
+            let previous = polygon[(i + length - 1) % length].1;
+            let current = polygon[i].1;
+            let next = polygon[(i + 1) % length].1;
+            if get_signed_area(previous, current, next).signum() != orientation {
+                return false;
+            }
+
+            // no other vertex inside or on the ear
+            polygon.iter().all(|&(_, point)| {
+                point == previous
+                    || point == current
+                    || point == next
+                    || [
+                        get_signed_area(current, next, point),
+                        get_signed_area(next, previous, point),
+                        get_signed_area(previous, current, point),
+                    ]
+                    .iter()
+                    .any(|area| area.signum() == -orientation)
+            })
+        })?;
+
+        let previous = polygon[(ear + length - 1) % length].0;
+        let current = polygon[ear].0;
+        let next = polygon[(ear + 1) % length].0;
+        if orientation > 0 {
+            triangles.push((previous, current, next));
+        } else {
+            triangles.push((next, current, previous));
+        }
+        polygon.remove(ear);
+    }
+
+    Some(triangles)
+}
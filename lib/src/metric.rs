@@ -0,0 +1,53 @@
+use crate::Point;
+
+/// Error of a pixel between the height predicted by its triangle and the actual height,
+/// which refinement keeps below the maximum error.
+///
+/// The metric is a generic parameter, so it is inlined into the rasterization of triangles
+/// without the cost of dynamic dispatch. It is shared between threads with the `rayon`
+/// feature, so it has to be `Sync`.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate, triangulate_with_metric, Error, ErrorMetric};
+///
+/// /// Relative error, so lowlands are refined more than peaks.
+/// struct RelativeHeightError;
+///
+/// impl ErrorMetric for RelativeHeightError {
+///     fn error(&self, predicted: f64, actual: f64, _point: (usize, usize)) -> f64 {
+///         (predicted - actual).abs() / actual.abs().max(1.0)
+///     }
+/// }
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) as f64 / 3.0).sin() * 50.0 + (i / 33) as f64 * 20.0)
+///     .collect::<Vec<f64>>();
+///
+/// let (points, _) = triangulate_with_metric(&heights, (33, 33), Error(0.01), RelativeHeightError)?;
+/// let (all_points, _) = triangulate(&heights, (33, 33), Error(0.0))?;
+/// assert!(points.len() < all_points.len());
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub trait ErrorMetric: Sync {
+    /// Non-negative error of the pixel.
+    ///
+    /// # Arguments
+    ///
+    /// * `predicted` - Height interpolated from the vertices of the triangle.
+    /// * `actual` - Height of the grid at the pixel.
+    /// * `point` - Position of the pixel in the grid.
+    fn error(&self, predicted: f64, actual: f64, point: Point) -> f64;
+}
+
+/// Absolute vertical difference between predicted and actual height, the default metric.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AbsoluteError;
+
+impl ErrorMetric for AbsoluteError {
+    #[inline]
+    fn error(&self, predicted: f64, actual: f64, _point: Point) -> f64 {
+        (predicted - actual).abs()
+    }
+}
@@ -0,0 +1,1041 @@
+use crate::error::TriangulationError;
+use crate::triangulation::Triangulation;
+use crate::{
+    check_finite_heights, create_triangulation, AbsoluteError, CancellationToken, Error,
+    ErrorMetric, ErrorThreshold, HeightValue, Mesh, MeshSink, Point, Refiner, StopCriterion,
+    Triangle, TriangulationStats,
+};
+
+/// Options of the triangulation process, built step by step and run on a grid.
+///
+/// Refinement stops once the maximum error is below [`TriangulationOptions::max_error`]
+/// or any of the size limits is reached, whichever comes first. Free functions like
+/// [`triangulate`](crate::triangulate) are shortcuts for the most common options.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{Error, TriangulationOptions};
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+///     .collect::<Vec<f64>>();
+///
+/// let (points, triangles) = TriangulationOptions::new()
+///     .max_error(Error(0.5))
+///     .max_points(100)
+///     .max_triangles(150)
+///     .run(&heights, 33, 33)?;
+/// assert!(points.len() <= 100);
+/// assert!(triangles.len() <= 151);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TriangulationOptions<'a> {
+    max_error: ErrorThreshold,
+    max_points: Option<usize>,
+    max_triangles: Option<usize>,
+    confidence: Option<&'a [f64]>,
+    preserve_extrema: bool,
+    seed_points: Option<&'a [Point]>,
+    breaklines: Option<&'a [(Point, Point)]>,
+    jitter_seed: Option<u64>,
+    max_edge_length: Option<f64>,
+    cell_size: Option<(f64, f64)>,
+    circumcircle_tolerance: Option<f64>,
+    min_density: Option<usize>,
+    clip_below: Option<f64>,
+    nodata: Option<f64>,
+    cover_valid_pixels: bool,
+    stop_criterion: StopCriterion,
+    skip_height_check: bool,
+    capacity_hint: Option<usize>,
+    cancellation: Option<CancellationToken>,
+    flip_y: bool,
+}
+
+impl<'a> TriangulationOptions<'a> {
+    /// Creates options refining until the maximum error is zero, without any limits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The maximum allowable error, see [`triangulate`](crate::triangulate).
+    ///
+    /// Accepts [`RelativeError`](crate::RelativeError) as well, which is converted to absolute
+    /// error from the elevation range of the grid before refinement. The range (max - min) is
+    /// computed once up front, e.g. `RelativeError(0.01)` is 1% of the range. Flat grids have
+    /// zero range and keep the initial two triangles.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, Error, RelativeError, TriangulationOptions};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    /// let range = heights.iter().fold(f64::MIN, |a, &b| a.max(b))
+    ///     - heights.iter().fold(f64::MAX, |a, &b| a.min(b));
+    /// let options = TriangulationOptions::new().max_error(RelativeError(0.01));
+    ///
+    /// // the same mesh regardless of the vertical scale
+    /// let scaled_heights = heights.iter().map(|height| height * 64.0).collect::<Vec<f64>>();
+    /// assert_eq!(
+    ///     options.run(&heights, 33, 33)?,
+    ///     options.run(&scaled_heights, 33, 33)?
+    /// );
+    /// assert_eq!(
+    ///     options.run(&heights, 33, 33)?,
+    ///     triangulate(&heights, (33, 33), Error(range * 0.01))?
+    /// );
+    ///
+    /// let (_, triangles) = options.run(&[5.0; 16], 4, 4)?;
+    /// assert_eq!(triangles.len(), 2);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn max_error(mut self, max_error: impl Into<ErrorThreshold>) -> Self {
+        self.max_error = max_error.into();
+        self
+    }
+
+    /// The maximum number of vertices, refinement stops once the mesh has `max_points` of them.
+    ///
+    /// Every refinement step adds one vertex, so the cap is met exactly unless the error threshold
+    /// is reached earlier. The four grid corners are always added, so caps below four return
+    /// the initial two triangle mesh.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let (points, _) = TriangulationOptions::new()
+    ///     .max_points(50)
+    ///     .run(&heights, 33, 33)?;
+    /// assert_eq!(points.len(), 50);
+    ///
+    /// let (points, triangles) = TriangulationOptions::new()
+    ///     .max_points(2)
+    ///     .run(&heights, 33, 33)?;
+    /// assert_eq!((points.len(), triangles.len()), (4, 2));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn max_points(mut self, max_points: usize) -> Self {
+        self.max_points = Some(max_points);
+        self
+    }
+
+    /// The maximum number of triangles, refinement stops once the mesh has at least
+    /// `max_triangles` of them.
+    ///
+    /// The cap is checked between refinement steps to keep the mesh consistent. A step splits
+    /// a triangle into three, or the two triangles sharing an edge into four when the new vertex
+    /// lies on that edge, so it adds at most two triangles and the final count may overshoot
+    /// the cap by one. The initial two triangles are always created.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::TriangulationOptions;
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let (_, triangles) = TriangulationOptions::new()
+    ///     .max_triangles(100)
+    ///     .run(&heights, 33, 33)?;
+    /// assert!((100..=101).contains(&triangles.len()));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn max_triangles(mut self, max_triangles: usize) -> Self {
+        self.max_triangles = Some(max_triangles);
+        self
+    }
+
+    /// Confidence of each height value, with the same layout as the height data.
+    ///
+    /// Error of every sample is multiplied by its confidence before comparing, so samples with low
+    /// confidence (e.g. noisy LiDAR returns) don't attract vertices. Confidence of `0.0` ignores
+    /// the sample completely, `1.0` keeps the plain error. Note that the maximum error is compared
+    /// against weighted errors. Negative or non-finite confidence would reverse or poison the
+    /// order of errors, so running fails with `InvalidConfidence` instead.
+    ///
+    /// There is no separate importance mask, samples are boosted by an [`ErrorMetric`] returning
+    /// larger errors for important pixels, see [`TriangulationOptions::run_with_metric`]. When both
+    /// are given, the error of the metric is multiplied by the confidence, so importance and
+    /// confidence combine as a product and zero confidence still ignores an important sample.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationError, TriangulationOptions};
+    ///
+    /// // flat grid with a single noise spike in the middle
+    /// let mut heights = vec![10.0; 81];
+    /// heights[40] = 100.0;
+    /// let mut confidence = vec![1.0; 81];
+    /// confidence[40] = 0.0;
+    ///
+    /// let (points, _) = TriangulationOptions::new()
+    ///     .max_error(Error(0.5))
+    ///     .confidence(&confidence)
+    ///     .run(&heights, 9, 9)?;
+    /// assert!(!points.contains(&(4, 4)));
+    ///
+    /// confidence[40] = f64::NAN;
+    /// assert!(matches!(
+    ///     TriangulationOptions::new().confidence(&confidence).run(&heights, 9, 9),
+    ///     Err(TriangulationError::InvalidConfidence { index: 40 })
+    /// ));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn confidence(mut self, confidence: &'a [f64]) -> Self {
+        self.confidence = Some(confidence);
+        self
+    }
+
+    /// Forces every local extremum of the grid to be a vertex.
+    ///
+    /// Local extremum is a pixel strictly higher (peak) or strictly lower (pit) than all of its
+    /// 8 neighbours; pixels on the grid border are compared only with neighbours inside the grid.
+    /// Flat extrema, where a pixel is equal to one of its neighbours, are not forced.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// // flat grid with a small sharp peak
+    /// let mut heights = vec![10.0; 81];
+    /// heights[3 * 9 + 5] = 10.5;
+    ///
+    /// let options = TriangulationOptions::new().max_error(Error(1.0));
+    /// let (points, _) = options.run(&heights, 9, 9)?;
+    /// assert!(!points.contains(&(5, 3)));
+    ///
+    /// let (points, _) = options.preserve_extrema(true).run(&heights, 9, 9)?;
+    /// assert!(points.contains(&(5, 3)));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn preserve_extrema(mut self, preserve_extrema: bool) -> Self {
+        self.preserve_extrema = preserve_extrema;
+        self
+    }
+
+    /// Points forced to be vertices, e.g. control points for registration which must be in the
+    /// mesh regardless of their error.
+    ///
+    /// Seeds are inserted one by one after the four grid corners, before the refinement.
+    /// Seeds on an existing edge split both triangles sharing it, duplicate seeds and seeds at
+    /// the corners are ignored. Running fails with `InvalidSeedPointError` if any of the seed
+    /// points is outside of the grid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, Mesh, TriangulationOptions};
+    ///
+    /// let heights = vec![10.0; 17 * 17];
+    /// // (8, 8) is on the diagonal of the initial two triangles
+    /// let seeds = [(3, 12), (8, 8), (3, 12), (16, 16)];
+    /// let (points, triangles) = TriangulationOptions::new()
+    ///     .max_error(Error(1.0))
+    ///     .seed_points(&seeds)
+    ///     .run(&heights, 17, 17)?;
+    ///
+    /// assert_eq!(points.len(), 6);
+    /// assert!(seeds.iter().all(|seed| points.contains(seed)));
+    /// assert!(Mesh::from_triangles(points, &triangles).verify_consistency(&heights, 17));
+    ///
+    /// let outside = [(17, 0)];
+    /// assert!(TriangulationOptions::new().seed_points(&outside).run(&heights, 17, 17).is_err());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn seed_points(mut self, seed_points: &'a [Point]) -> Self {
+        self.seed_points = Some(seed_points);
+        self
+    }
+
+    /// Segments followed by edges of the mesh, e.g. ridges, stream centerlines or road edges
+    /// which must not be cut across by triangles.
+    ///
+    /// Both ends of each segment become vertices and the segment is made of edges which are
+    /// never flipped during refinement. Vertices are grid pixels, so a segment which doesn't
+    /// pass through pixels is followed by a chain of edges through pixels nearest to it.
+    /// Breaklines are inserted after seed points, before the refinement; crossing breaklines are
+    /// not supported and one of them may miss edges near the crossing. Running fails with
+    /// `InvalidBreaklineError` if any of the breakline ends is outside of the grid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// let heights = vec![10.0; 17 * 17];
+    /// let breaklines = [((1, 8), (15, 8))];
+    /// // checks whether an edge crosses the breakline between its ends
+    /// let crosses = |points: &[(usize, usize)], triangles: &[(usize, usize, usize)]| {
+    ///     triangles.iter().any(|&(a, b, c)| {
+    ///         [(a, b), (b, c), (c, a)].iter().any(|&(u, v)| {
+    ///             let (u, v) = (points[u], points[v]);
+    ///             let (uy, vy) = (u.1 as f64 - 8.0, v.1 as f64 - 8.0);
+    ///             let x = u.0 as f64 + uy / (uy - vy) * (v.0 as f64 - u.0 as f64);
+    ///             uy * vy < 0.0 && x > 1.0 && x < 15.0
+    ///         })
+    ///     })
+    /// };
+    ///
+    /// let options = TriangulationOptions::new().max_error(Error(1.0));
+    /// let (points, triangles) = options.run(&heights, 17, 17)?;
+    /// assert!(crosses(&points, &triangles));
+    ///
+    /// let (points, triangles) = options.breaklines(&breaklines).run(&heights, 17, 17)?;
+    /// assert!(points.contains(&breaklines[0].0) && points.contains(&breaklines[0].1));
+    /// assert!(!crosses(&points, &triangles));
+    ///
+    /// let outside = [((0, 0), (0, 17))];
+    /// assert!(TriangulationOptions::new().breaklines(&outside).run(&heights, 17, 17).is_err());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn breaklines(mut self, breaklines: &'a [(Point, Point)]) -> Self {
+        self.breaklines = Some(breaklines);
+        self
+    }
+
+    /// Seed for breaking ties between equal error candidates pseudo-randomly.
+    ///
+    /// On large plateaus many pixels have the same error and by default the first one in scan
+    /// order is chosen, which produces visible axis-aligned artifacts. With jitter the choice
+    /// depends on a hash of the pixel and the seed, so the output differs slightly from
+    /// [`triangulate`](crate::triangulate), but is the same for the same seed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, Error, TriangulationOptions};
+    ///
+    /// // plateau in the middle of a flat grid
+    /// let heights = (0..32 * 32)
+    ///     .map(|i| if (8..24).contains(&(i % 32)) && (8..24).contains(&(i / 32)) { 10.0 } else { 0.0 })
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let options = TriangulationOptions::new().max_error(Error(0.5)).jitter_seed(42);
+    /// let jittered = options.run(&heights, 32, 32)?;
+    /// assert_eq!(jittered, options.run(&heights, 32, 32)?);
+    /// assert_ne!(jittered, triangulate(&heights, (32, 32), Error(0.5))?);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter_seed = Some(seed);
+        self
+    }
+
+    /// The maximum edge length in pixels, or in world units with
+    /// [`TriangulationOptions::cell_size`].
+    ///
+    /// A triangle with a too long edge is split at the pixel closest to the middle of its longest
+    /// edge, so it keeps getting refined even in flat regions. Whichever of the error and the
+    /// length requires more vertices wins. Edges can't be shorter than the pixel grid allows,
+    /// so lengths below `sqrt(2)` can't be guaranteed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// let heights = vec![10.0; 17 * 17];
+    /// let (points, triangles) = TriangulationOptions::new()
+    ///     .max_error(Error(1.0))
+    ///     .max_edge_length(3.0)
+    ///     .run(&heights, 17, 17)?;
+    ///
+    /// for (a, b, c) in triangles {
+    ///     for (start, end) in [(a, b), (b, c), (c, a)] {
+    ///         let delta_x = points[start].0 as f64 - points[end].0 as f64;
+    ///         let delta_y = points[start].1 as f64 - points[end].1 as f64;
+    ///         assert!(delta_x.hypot(delta_y) <= 3.0);
+    ///     }
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn max_edge_length(mut self, max_edge_length: f64) -> Self {
+        self.max_edge_length = Some(max_edge_length);
+        self
+    }
+
+    /// Ground size of a pixel in x and y, e.g. `(30.0, 10.0)` meters, for grids with different
+    /// ground resolution in x and y.
+    ///
+    /// Delaunay circumcircle test is done in world units, pixel `(x, y)` being at
+    /// `(x * cell_size.0, y * cell_size.1)`, so triangles are well shaped on the ground rather
+    /// than in pixels. Maximum edge length is in world units as well. The error is vertical,
+    /// so it doesn't depend on the cell size. Returned points are still grid coordinates,
+    /// [`Mesh::scaled_points`] converts them to world units. Running fails with
+    /// `InvalidCellSizeError` if any of the sizes is not positive and finite.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, Error, TriangulationOptions};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    /// let options = TriangulationOptions::new().max_error(Error(1.0));
+    ///
+    /// let square = options.clone().cell_size((10.0, 10.0)).run(&heights, 33, 33)?;
+    /// assert_eq!(square, triangulate(&heights, (33, 33), Error(1.0))?);
+    ///
+    /// let stretched = options.clone().cell_size((30.0, 10.0)).run(&heights, 33, 33)?;
+    /// assert_ne!(stretched, square);
+    ///
+    /// assert!(options.cell_size((0.0, 10.0)).run(&heights, 33, 33).is_err());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn cell_size(mut self, cell_size: (f64, f64)) -> Self {
+        self.cell_size = Some(cell_size);
+        self
+    }
+
+    /// Margin of the circumcircle test below which edges are left unflipped, for keeping
+    /// refinement of nearly cocircular points stable.
+    ///
+    /// An edge is flipped when the determinant of the circumcircle test is below `-tolerance`
+    /// instead of below zero, so points only slightly inside the circumcircle of a triangle keep
+    /// it. The determinant is in units of coordinates to the fourth power, pixels without
+    /// [`TriangulationOptions::cell_size`] and world units with it. Negative tolerance is
+    /// treated as zero.
+    ///
+    /// Without the tolerance the test on pixels is evaluated exactly in integers, which already
+    /// treats exactly cocircular points as not inside. Setting it evaluates the determinant in
+    /// `f64` instead, which is exact only while it fits the mantissa, for grids up to a few
+    /// thousand pixels wide. On pixels the determinant is an integer, so tolerance below one
+    /// changes the result only where `f64` rounds. With the `robust-predicates` feature the
+    /// sign of the determinant on pixels is exact on any grid and only the margin is
+    /// approximate.
+    ///
+    /// With a cell size the determinant is always evaluated in plain `f64`, with or without
+    /// the feature, so the sign for nearly cocircular points may be wrong either way and the
+    /// mesh may not be exactly Delaunay. Only convex quads have nearly cocircular points, so a
+    /// misjudged flip still doesn't make a triangle degenerate or reversed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::predicates::signed_area;
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let options = TriangulationOptions::new().max_error(Error(1.0));
+    /// let tolerant = options.clone().circumcircle_tolerance(0.5).run(&heights, 33, 33)?;
+    /// assert_eq!(tolerant, options.run(&heights, 33, 33)?);
+    ///
+    /// // nearly square cells make neighbouring pixels nearly cocircular
+    /// let options = options.cell_size((1.0, 0.99));
+    /// let tolerant = options.clone().circumcircle_tolerance(1.0).run_mesh(&heights, 33, 33)?;
+    /// assert_ne!(tolerant, options.run_mesh(&heights, 33, 33)?);
+    /// assert_eq!(tolerant.validate(), Ok(()));
+    /// assert!(tolerant.triangles().iter().all(|&(a, b, c)| {
+    ///     signed_area(tolerant.points()[a], tolerant.points()[b], tolerant.points()[c]) > 0
+    /// }));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn circumcircle_tolerance(mut self, tolerance: f64) -> Self {
+        self.circumcircle_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Size of blocks of the grid which must contain at least one vertex.
+    ///
+    /// After the usual refinement, a vertex is inserted in the center of every `block_size` x
+    /// `block_size` block without vertices, which gives predictable minimum density for renderers.
+    /// Blocks at the right and bottom edges of the grid may be smaller. This increases vertex count
+    /// and reduces the benefit of adaptive refinement on flat areas. Block size of `0` is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// let heights = vec![10.0; 20 * 20];
+    /// let (points, _) = TriangulationOptions::new()
+    ///     .max_error(Error(1.0))
+    ///     .min_density(8)
+    ///     .run(&heights, 20, 20)?;
+    ///
+    /// for block_y in 0..3 {
+    ///     for block_x in 0..3 {
+    ///         assert!(points
+    ///             .iter()
+    ///             .any(|point| point.0 / 8 == block_x && point.1 / 8 == block_y));
+    ///     }
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn min_density(mut self, block_size: usize) -> Self {
+        self.min_density = Some(block_size);
+        self
+    }
+
+    /// Height of the waterline, everything below it is treated as a flat sea level.
+    ///
+    /// For the error metric, heights below `clip_below` are taken as equal to it, so underwater
+    /// areas become a flat plane with few vertices instead of being refined. Unlike dropping
+    /// nodata, underwater areas stay covered by triangles. Only the refinement is affected:
+    /// the returned points are grid coordinates, so exporters looking up heights in the grid
+    /// write the real underwater heights unless the data is clipped the same way before export.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// // bumpy basin below sea level on the left, ramp rising from the shore on the right
+    /// let heights = (0..17 * 17)
+    ///     .map(|i| {
+    ///         let (x, y) = (i % 17, i / 17);
+    ///         if x < 8 {
+    ///             -1.0 - ((x * 7 + y * 13) % 5) as f64
+    ///         } else {
+    ///             (x - 8) as f64
+    ///         }
+    ///     })
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let options = TriangulationOptions::new().max_error(Error(0.5));
+    /// let (_, triangles) = options.run(&heights, 17, 17)?;
+    /// let (_, clipped_triangles) = options.clip_below(0.0).run(&heights, 17, 17)?;
+    /// assert!(clipped_triangles.len() <= 8);
+    /// assert!(clipped_triangles.len() < triangles.len());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn clip_below(mut self, clip_below: f64) -> Self {
+        self.clip_below = Some(clip_below);
+        self
+    }
+
+    /// Sentinel height of void pixels, e.g. `-9999.0` or `NaN` for ocean or clouds in a DEM.
+    ///
+    /// Pixels equal to `nodata` don't contribute to the error, so triangles entirely over voids
+    /// are never refined and triangles partially over them measure error on valid pixels only.
+    /// `NaN` sentinel matches any `NaN` height, which is then not rejected as non-finite.
+    /// Vertices on void pixels can't predict heights, so valid pixels of triangles touching them
+    /// are interpolated from the valid vertices only. Triangles without any valid vertex are split
+    /// first, so the error is infinite only when a size limit stops refinement before that.
+    /// Grid corners are always vertices, even when they are void, see
+    /// [`triangulate_regions`](crate::triangulate_regions) for meshes of valid regions only.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// // flat land on the left, noisy void on the right
+    /// let heights = (0..17 * 17)
+    ///     .map(|i| {
+    ///         let (x, y) = (i % 17, i / 17);
+    ///         if x <= 8 {
+    ///             10.0
+    ///         } else if (x + y) % 2 == 0 {
+    ///             -9999.0
+    ///         } else {
+    ///             f64::NAN
+    ///         }
+    ///     })
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let options = TriangulationOptions::new().max_error(Error(0.5));
+    /// assert!(options.run(&heights, 17, 17).is_err());
+    ///
+    /// let (points, _) = options.nodata(f64::NAN).run(&heights, 17, 17)?;
+    /// assert!(points.len() < 17 * 17 / 2);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn nodata(mut self, nodata: f64) -> Self {
+        self.nodata = Some(nodata);
+        self
+    }
+
+    /// Refines valid pixels until triangles of valid vertices cover them, so dropping triangles
+    /// with void vertices keeps every valid pixel, see [`triangulate_regions`](crate::triangulate_regions).
+    pub(crate) fn cover_valid_pixels(mut self, cover_valid_pixels: bool) -> Self {
+        self.cover_valid_pixels = cover_valid_pixels;
+        self
+    }
+
+    /// Condition at which refinement stops, see [`StopCriterion`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, Error, Mesh, StopCriterion, TriangulationOptions};
+    ///
+    /// // smooth surface with a single spike
+    /// let mut heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 9.0).sin() * 10.0)
+    ///     .collect::<Vec<f64>>();
+    /// heights[16 * 33 + 16] = 1000.0;
+    ///
+    /// let (points, triangles) = TriangulationOptions::new()
+    ///     .stop_criterion(StopCriterion::Rmse(0.5))
+    ///     .run(&heights, 33, 33)?;
+    /// let mesh = Mesh::from_triangles(points.clone(), &triangles);
+    /// let squared_error_sum = (0..33 * 33)
+    ///     .map(|i| {
+    ///         let (x, y) = (i % 33, i / 33);
+    ///         let z = mesh.sample(&heights, 33, x as f64, y as f64).unwrap();
+    ///         (z - heights[i]).powi(2)
+    ///     })
+    ///     .sum::<f64>();
+    /// assert!((squared_error_sum / (33.0 * 33.0)).sqrt() <= 0.5);
+    ///
+    /// let (max_error_points, _) = triangulate(&heights, (33, 33), Error(0.5))?;
+    /// assert!(points.len() < max_error_points.len());
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn stop_criterion(mut self, stop_criterion: StopCriterion) -> Self {
+        self.stop_criterion = stop_criterion;
+        self
+    }
+
+    /// Expected number of vertices, storage for them and their triangles is allocated
+    /// up front instead of growing during refinement.
+    ///
+    /// Without the hint storage is presized for [`TriangulationOptions::max_points`], or half of
+    /// [`TriangulationOptions::max_triangles`], when either is set, as a mesh of `n` vertices has
+    /// less than `2 * n` triangles. The number of vertices needed for the max error depends on
+    /// the roughness of the terrain, so a hint from a previous run on similar grids works best.
+    /// Any hint is capped at the number of pixels. A wrong hint only costs memory or reallocations.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let options = TriangulationOptions::new().max_error(Error(1.0));
+    /// let hinted = options.clone().capacity_hint(400).run(&heights, 33, 33)?;
+    /// assert_eq!(hinted, options.run(&heights, 33, 33)?);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn capacity_hint(mut self, expected_vertices: usize) -> Self {
+        self.capacity_hint = Some(expected_vertices);
+        self
+    }
+
+    /// Token for aborting the triangulation from another thread, see [`CancellationToken`].
+    ///
+    /// Cancelled triangulation returns `Cancelled` error with the mesh refined so far.
+    pub fn cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Flips output points to `(x, height - 1 - y)`, for consumers expecting y growing upwards
+    /// instead of downwards like rows of the grid.
+    ///
+    /// Only the output is flipped, heights of [`Mesh`] methods and of
+    /// [`triangulate_with_heights`](crate::triangulate_with_heights)-like output are still
+    /// looked up in the original grid, which has to be flipped for looking up flipped points.
+    /// Triangles are reversed from `(a, b, c)` to `(a, c, b)`, so their winding is the same on
+    /// screen in both modes: counterclockwise when drawn with y growing downwards without the
+    /// flip and with y growing upwards with it.
+    /// [`signed_area`](crate::predicates::signed_area) of output triangles is positive either way.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::predicates::signed_area;
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// let heights = (0..9 * 5).map(|i| ((i % 9) * (i / 9)) as f64).collect::<Vec<f64>>();
+    /// let options = TriangulationOptions::new().max_error(Error(0.5));
+    /// let (points, triangles) = options.run(&heights, 9, 5)?;
+    /// let (flipped_points, flipped_triangles) = options.flip_y(true).run(&heights, 9, 5)?;
+    ///
+    /// for (point, flipped) in points.iter().zip(&flipped_points) {
+    ///     assert_eq!(*flipped, (point.0, 4 - point.1));
+    /// }
+    /// for (&(a, b, c), &flipped) in triangles.iter().zip(&flipped_triangles) {
+    ///     assert_eq!(flipped, (a, c, b));
+    ///     let [a, b, c] = [a, c, b].map(|vertex| flipped_points[vertex]);
+    ///     assert!(signed_area(a, b, c) >= 0);
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+
+    /// Skips scanning heights for `NaN` and infinity before the triangulation.
+    ///
+    /// The scan is a single pass over the grid, which may be worth saving for huge grids
+    /// known to be finite. Non-finite heights silently produce a wrong mesh when skipped.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationError, TriangulationOptions};
+    ///
+    /// let heights = [0.0, 1.0, f64::NAN, 3.0];
+    /// let result = TriangulationOptions::new().run(&heights, 2, 2);
+    /// assert!(matches!(result, Err(TriangulationError::NonFiniteHeight { index: 2 })));
+    ///
+    /// let finite_heights = [0.0, 1.0, 2.0, 3.0];
+    /// TriangulationOptions::new()
+    ///     .skip_height_check(true)
+    ///     .run(&finite_heights, 2, 2)?;
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn skip_height_check(mut self, skip_height_check: bool) -> Self {
+        self.skip_height_check = skip_height_check;
+        self
+    }
+
+    /// Runs the triangulation process with these options.
+    ///
+    /// Heights can be of any [`HeightValue`] type, e.g. `f32` or `u16` grids don't need to be converted.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid.
+    /// * `width` - The width of the grid.
+    /// * `height` - The height of the grid.
+    ///
+    /// # Returns
+    ///
+    /// Tuple of points and triangles, same as [`triangulate`](crate::triangulate).
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidDataLengthError` - If the length of the height data or confidence does not match the width and height of the grid.
+    /// - `InvalidConfidence` - If any confidence is negative, `NaN` or infinite, with the index of the first one.
+    /// - `InvalidSeedPointError` - If any of the seed points is outside of the grid.
+    /// - `InvalidBreaklineError` - If any of the breakline ends is outside of the grid.
+    /// - `InvalidCellSizeError` - If any of the cell sizes is not positive and finite.
+    /// - `GridTooSmall` - If the width or height of the grid is less than 2.
+    /// - `GridTooLarge` - If the grid has too many pixels for the `u32-indices` feature.
+    /// - `NonFiniteHeight` - If any height is `NaN` or infinite, unless the check is skipped.
+    /// - `MaxErrorRetrievalError` - If the maximum error is not found in the priority queue.
+    /// - `EmptyQueueError` - If the priority queue is empty during triangulation.
+    /// - `Cancelled` - If the cancellation token is cancelled, with the mesh refined so far.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, Error, TriangulationOptions};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f32 / 3.0).sin() * ((i / 33) as f32 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f32>>();
+    /// let (points, triangles) = TriangulationOptions::new()
+    ///     .max_error(Error(1.0))
+    ///     .run(&heights, 33, 33)?;
+    ///
+    /// let heights = heights.iter().map(|&height| height as f64).collect::<Vec<f64>>();
+    /// assert_eq!((points, triangles), triangulate(&heights, (33, 33), Error(1.0))?);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn run<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        height: usize,
+    ) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+        let delatin = self.triangulate(height_data, (width, height), AbsoluteError)?;
+
+        Ok((delatin.get_vertext_points(), delatin.get_triangle_indices()))
+    }
+
+    /// Same as [`TriangulationOptions::run`], but returns a [`Mesh`] which keeps connectivity of triangles.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`TriangulationOptions::run`].
+    pub fn run_mesh<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        height: usize,
+    ) -> Result<Mesh, TriangulationError> {
+        Ok(self
+            .triangulate(height_data, (width, height), AbsoluteError)?
+            .mesh())
+    }
+
+    /// Same as [`TriangulationOptions::run`], but passes points and triangles to the sink
+    /// instead of returning them, see [`MeshSink`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`TriangulationOptions::run`]. Nothing is passed to the sink on error.
+    pub fn run_streaming<H: HeightValue, S: MeshSink + ?Sized>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        height: usize,
+        sink: &mut S,
+    ) -> Result<(), TriangulationError> {
+        self.triangulate(height_data, (width, height), AbsoluteError)?
+            .emit(sink);
+
+        Ok(())
+    }
+
+    /// Same as [`TriangulationOptions::run`], but measures errors of pixels with the metric
+    /// instead of the absolute difference of heights, see [`ErrorMetric`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`TriangulationOptions::run`].
+    pub fn run_with_metric<H: HeightValue, M: ErrorMetric>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        height: usize,
+        metric: M,
+    ) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+        let delatin = self.triangulate(height_data, (width, height), metric)?;
+
+        Ok((delatin.get_vertext_points(), delatin.get_triangle_indices()))
+    }
+
+    /// Same as [`TriangulationOptions::run_mesh`], also returning summary of the refinement.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`TriangulationOptions::run`].
+    pub fn run_with_stats<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        height: usize,
+    ) -> Result<(Mesh, TriangulationStats), TriangulationError> {
+        let delatin = self.triangulate(height_data, (width, height), AbsoluteError)?;
+
+        Ok((delatin.mesh(), delatin.stats()))
+    }
+
+    /// Same as [`TriangulationOptions::run`], calling `progress` after every refinement step.
+    ///
+    /// Callback receives the current maximum error and vertex count, e.g. for driving a progress
+    /// bar by how the error decays towards the target. It only gets copies of the values,
+    /// so it can't change the triangulation. [`TriangulationOptions::run`] doesn't call any
+    /// callback, so it has no overhead.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`TriangulationOptions::run`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, TriangulationOptions};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let mut errors = Vec::new();
+    /// let (points, _) = TriangulationOptions::new()
+    ///     .max_error(Error(1.0))
+    ///     .run_with_progress(&heights, 33, 33, |max_error, vertex_count| {
+    ///         errors.push((max_error, vertex_count));
+    ///     })?;
+    ///
+    /// assert_eq!(errors.len(), points.len() - 4);
+    /// assert!(errors.last().unwrap().0 <= Error(1.0));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn run_with_progress<H: HeightValue>(
+        &self,
+        height_data: &[H],
+        width: usize,
+        height: usize,
+        mut progress: impl FnMut(Error, usize),
+    ) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+        let delatin = self.triangulate_with_progress(
+            height_data,
+            (width, height),
+            |max_error, vertex_count, _| progress(max_error, vertex_count),
+        )?;
+
+        Ok((delatin.get_vertext_points(), delatin.get_triangle_indices()))
+    }
+
+    /// Creates a refiner with these options for refining step by step, e.g. to animate mesh growth.
+    ///
+    /// [`Refiner::step`] refines towards [`TriangulationOptions::max_error`] and honors the size
+    /// limits. Minimum density is applied only by [`TriangulationOptions::run`].
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidDataLengthError` - If the length of the height data or confidence does not match the width and height of the grid.
+    /// - `InvalidConfidence` - If any confidence is negative, `NaN` or infinite, with the index of the first one.
+    /// - `GridTooSmall` - If the width or height of the grid is less than 2.
+    /// - `GridTooLarge` - If the grid has too many pixels for the `u32-indices` feature.
+    /// - `NonFiniteHeight` - If any height is `NaN` or infinite, unless the check is skipped.
+    pub fn start<'h, H: HeightValue>(
+        &self,
+        height_data: &'h [H],
+        width: usize,
+        height: usize,
+    ) -> Result<Refiner<'h, H>, TriangulationError>
+    where
+        'a: 'h,
+    {
+        let mut delatin = self.configure(height_data, (width, height), AbsoluteError)?;
+        delatin.initialize();
+        Ok(Refiner::from_triangulation(
+            delatin,
+            (width, height),
+            self.max_error,
+        ))
+    }
+
+    /// Creates the triangulation with these options and the error metric and refines it.
+    pub(crate) fn triangulate<'h, H: HeightValue, M: ErrorMetric>(
+        &self,
+        height_data: &'h [H],
+        dimesions: (usize, usize),
+        metric: M,
+    ) -> Result<Triangulation<'h, H, M>, TriangulationError>
+    where
+        'a: 'h,
+    {
+        let mut delatin = self.configure(height_data, dimesions, metric)?;
+        delatin.run(delatin.absolute_error(self.max_error))?;
+        if let Some(block_size) = self.min_density {
+            delatin.fill_empty_blocks(block_size);
+        }
+
+        Ok(delatin)
+    }
+
+    /// Same as [`TriangulationOptions::triangulate`], reporting progress after every refinement step.
+    pub(crate) fn triangulate_with_progress<'h, H: HeightValue>(
+        &self,
+        height_data: &'h [H],
+        dimesions: (usize, usize),
+        progress: impl FnMut(Error, usize, usize),
+    ) -> Result<Triangulation<'h, H>, TriangulationError>
+    where
+        'a: 'h,
+    {
+        let mut delatin = self.configure(height_data, dimesions, AbsoluteError)?;
+        delatin.initialize();
+        delatin.refine_until_with_progress(delatin.absolute_error(self.max_error), progress)?;
+        if let Some(block_size) = self.min_density {
+            delatin.fill_empty_blocks(block_size);
+        }
+
+        Ok(delatin)
+    }
+
+    /// Creates the triangulation with these options, without refining it.
+    fn configure<'h, H: HeightValue, M: ErrorMetric>(
+        &self,
+        height_data: &'h [H],
+        dimesions: (usize, usize),
+        metric: M,
+    ) -> Result<Triangulation<'h, H, M>, TriangulationError>
+    where
+        'a: 'h,
+    {
+        let mut delatin = create_triangulation(height_data, dimesions, metric)?;
+        if !self.skip_height_check {
+            check_finite_heights(height_data, self.nodata)?;
+        }
+        let expected_vertices = self.capacity_hint.or(self.max_points).or(self
+            .max_triangles
+            .map(|max_triangles| max_triangles / 2 + 2));
+        if let Some(expected_vertices) = expected_vertices {
+            delatin.reserve(expected_vertices.min(height_data.len()));
+        }
+        if let Some(confidence) = self.confidence {
+            (confidence.len() == height_data.len())
+                .then_some(())
+                .ok_or(TriangulationError::InvalidDataLengthError)?;
+            if let Some(index) = confidence
+                .iter()
+                .position(|weight| !weight.is_finite() || *weight < 0.0)
+            {
+                return Err(TriangulationError::InvalidConfidence { index });
+            }
+            delatin.set_confidence(confidence);
+        }
+        if let Some(seed_points) = self.seed_points {
+            seed_points
+                .iter()
+                .all(|point| point.0 < dimesions.0 && point.1 < dimesions.1)
+                .then_some(())
+                .ok_or(TriangulationError::InvalidSeedPointError)?;
+            delatin.add_seed_points(seed_points);
+        }
+        if let Some(breaklines) = self.breaklines {
+            breaklines
+                .iter()
+                .flat_map(|&(start, end)| [start, end])
+                .all(|point| point.0 < dimesions.0 && point.1 < dimesions.1)
+                .then_some(())
+                .ok_or(TriangulationError::InvalidBreaklineError)?;
+            delatin.add_breaklines(breaklines);
+        }
+        if self.preserve_extrema {
+            delatin.set_preserve_extrema(true);
+        }
+        if let Some(seed) = self.jitter_seed {
+            delatin.set_jitter_seed(seed);
+        }
+        if let Some(cell_size) = self.cell_size {
+            let is_valid = [cell_size.0, cell_size.1]
+                .iter()
+                .all(|size| size.is_finite() && *size > 0.0);
+            is_valid
+                .then_some(())
+                .ok_or(TriangulationError::InvalidCellSizeError)?;
+            delatin.set_cell_size(cell_size);
+        }
+        if let Some(tolerance) = self.circumcircle_tolerance {
+            delatin.set_circumcircle_tolerance(tolerance.max(0.0));
+        }
+        if let Some(max_edge_length) = self.max_edge_length {
+            delatin.set_max_edge_length(max_edge_length);
+        }
+        if let Some(max_points) = self.max_points {
+            delatin.set_max_points(max_points);
+        }
+        if let Some(max_triangles) = self.max_triangles {
+            delatin.set_max_triangles(max_triangles);
+        }
+        if let Some(clip_below) = self.clip_below {
+            delatin.set_clip_below(clip_below);
+        }
+        if let Some(nodata) = self.nodata {
+            delatin.set_nodata(nodata);
+            delatin.set_cover_valid_pixels(self.cover_valid_pixels);
+        }
+        if let StopCriterion::Rmse(rmse) = self.stop_criterion {
+            delatin.set_rmse_target(rmse);
+        }
+        if let Some(cancellation) = &self.cancellation {
+            delatin.set_cancellation(cancellation.clone());
+        }
+        delatin.set_flip_y(self.flip_y);
+
+        Ok(delatin)
+    }
+}
@@ -0,0 +1,118 @@
+/*!
+Geometric predicates used by the triangulation, exposed for visualization and debugging.
+*/
+
+use std::cmp::Ordering;
+
+use crate::utils::{get_signed_area, is_point_in_circumcircle, orientation};
+use crate::Point;
+
+/// Computes the circumcircle of the triangle formed by three points.
+///
+/// Calculations are done in `f64`, so large grid coordinates don't overflow.
+///
+/// # Arguments
+///
+/// * `point_a` - First vertex of the triangle.
+/// * `point_b` - Second vertex of the triangle.
+/// * `point_c` - Third vertex of the triangle.
+///
+/// # Returns
+///
+/// Tuple of the circumcircle center and radius, or `None` if the points are collinear.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::predicates::circumcircle;
+///
+/// // circumcenter of a right triangle is the midpoint of its hypotenuse
+/// let (center, radius) = circumcircle((0, 0), (4, 0), (0, 3)).unwrap();
+/// assert_eq!(center, (2.0, 1.5));
+/// assert_eq!(radius, 2.5);
+///
+/// assert!(circumcircle((0, 0), (1, 1), (2, 2)).is_none());
+/// ```
+pub fn circumcircle(point_a: Point, point_b: Point, point_c: Point) -> Option<((f64, f64), f64)> {
+    // translate to point_a to keep values small
+    let delta_x_b = point_b.0 as f64 - point_a.0 as f64;
+    let delta_y_b = point_b.1 as f64 - point_a.1 as f64;
+    let delta_x_c = point_c.0 as f64 - point_a.0 as f64;
+    let delta_y_c = point_c.1 as f64 - point_a.1 as f64;
+
+    let denominator = 2.0 * (delta_x_b * delta_y_c - delta_y_b * delta_x_c);
+    if denominator == 0.0 {
+        return None;
+    }
+
+    let square_distance_b = delta_x_b * delta_x_b + delta_y_b * delta_y_b;
+    let square_distance_c = delta_x_c * delta_x_c + delta_y_c * delta_y_c;
+
+    let center_x = (delta_y_c * square_distance_b - delta_y_b * square_distance_c) / denominator;
+    let center_y = (delta_x_b * square_distance_c - delta_x_c * square_distance_b) / denominator;
+    let radius = center_x.hypot(center_y);
+
+    Some((
+        (center_x + point_a.0 as f64, center_y + point_a.1 as f64),
+        radius,
+    ))
+}
+
+/// Computes twice the signed area of the triangle formed by three points.
+///
+/// Area is positive for the winding of triangles returned by the triangulation and zero for
+/// collinear points. Calculations are exact in `i64`, also for coordinates of large grids.
+///
+/// # Arguments
+///
+/// * `point_a` - First vertex of the triangle.
+/// * `point_b` - Second vertex of the triangle.
+/// * `point_c` - Third vertex of the triangle.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::predicates::signed_area;
+///
+/// // triangle spanning the full width of a 60000-wide grid
+/// assert_eq!(signed_area((0, 0), (0, 59999), (59999, 0)), 59999 * 59999);
+/// assert_eq!(signed_area((0, 0), (59999, 0), (0, 59999)), -59999 * 59999);
+/// assert_eq!(signed_area((0, 0), (30000, 30000), (59999, 59999)), 0);
+/// ```
+pub fn signed_area(point_a: Point, point_b: Point, point_c: Point) -> i64 {
+    get_signed_area(point_a, point_b, point_c)
+}
+
+/// Checks whether the point lies strictly inside the circumcircle of the triangle.
+///
+/// This is the test used to keep the triangulation Delaunay. Vertices may be given in any
+/// winding. With the `robust-predicates` feature it is evaluated with exact arithmetic
+/// on `f64` coordinates.
+///
+/// # Arguments
+///
+/// * `point` - The point to test.
+/// * `point_a` - First vertex of the triangle.
+/// * `point_b` - Second vertex of the triangle.
+/// * `point_c` - Third vertex of the triangle.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::predicates::in_circumcircle;
+///
+/// assert!(in_circumcircle((1, 1), (0, 0), (4, 0), (0, 4)));
+/// // points on the circle are not inside
+/// assert!(!in_circumcircle((4, 4), (0, 0), (4, 0), (0, 4)));
+///
+/// // coordinates of large grids are handled exactly
+/// assert!(in_circumcircle((69999, 69999), (0, 0), (70000, 0), (0, 70000)));
+/// assert!(!in_circumcircle((70000, 70000), (0, 0), (70000, 0), (0, 70000)));
+/// ```
+pub fn in_circumcircle(point: Point, point_a: Point, point_b: Point, point_c: Point) -> bool {
+    match orientation(point_a, point_b, point_c) {
+        Ordering::Greater => is_point_in_circumcircle(point, point_a, point_b, point_c),
+        Ordering::Less => is_point_in_circumcircle(point, point_a, point_c, point_b),
+        Ordering::Equal => false,
+    }
+}
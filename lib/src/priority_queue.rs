@@ -10,6 +10,8 @@ pub(crate) struct PriorityQueue {
     triangle_errors: Vec<Error>,
     /// Pending triangles to be processed
     pending_triangle_indices: Vec<usize>,
+    /// Vector of triangle indices to their positions in the pending triangles for faster removal
+    pending_triangle_positions: Vec<Option<usize>>,
 }
 
 impl PriorityQueue {
@@ -17,16 +19,55 @@ impl PriorityQueue {
         Self {
             triangle_queue_indices: vec![None; initial_queue_size],
             pending_triangle_indices: Vec::default(),
+            pending_triangle_positions: vec![None; initial_queue_size],
             triangle_queue: Vec::default(),
             triangle_errors: Vec::default(),
         }
     }
 
+    /// Empties the queue keeping allocated memory, so it can be reused like a new one.
+    pub fn clear(&mut self) {
+        self.triangle_queue.clear();
+        self.triangle_errors.clear();
+        self.pending_triangle_indices.clear();
+        self.pending_triangle_positions.fill(None);
+        self.triangle_queue_indices.fill(None);
+    }
+
+    /// Presizes the queue so it holds `triangle_count` triangles without reallocating.
+    pub fn reserve(&mut self, triangle_count: usize) {
+        self.triangle_queue
+            .reserve(triangle_count.saturating_sub(self.triangle_queue.len()));
+        self.triangle_errors
+            .reserve(triangle_count.saturating_sub(self.triangle_errors.len()));
+        self.pending_triangle_indices
+            .reserve(triangle_count.saturating_sub(self.pending_triangle_indices.len()));
+        if self.triangle_queue_indices.len() < triangle_count {
+            self.triangle_queue_indices.resize(triangle_count, None);
+        }
+        if self.pending_triangle_positions.len() < triangle_count {
+            self.pending_triangle_positions.resize(triangle_count, None);
+        }
+    }
+
     pub fn add_pending_triangle(&mut self, t: usize) {
+        if t >= self.pending_triangle_positions.len() {
+            let new_length = (t + 1).max(self.pending_triangle_positions.len() * 2);
+            self.pending_triangle_positions.resize(new_length, None);
+        }
+        if self.pending_triangle_positions[t].is_some() {
+            return;
+        }
+
+        self.pending_triangle_positions[t] = Some(self.pending_triangle_indices.len());
         self.pending_triangle_indices.push(t);
     }
 
     pub fn consume_pending_triangles(&mut self) -> Vec<usize> {
+        for &triangle_index in &self.pending_triangle_indices {
+            self.pending_triangle_positions[triangle_index] = None;
+        }
+
         self.pending_triangle_indices.drain(..).collect()
     }
 
@@ -34,12 +75,22 @@ impl PriorityQueue {
         self.triangle_errors.first()
     }
 
+    /// Error of the queued triangle, `None` for triangles which are not in the queue.
+    pub fn get_error(&self, triangle_index: usize) -> Option<Error> {
+        self.triangle_queue_indices
+            .get(triangle_index)
+            .copied()
+            .flatten()
+            .map(|index| self.triangle_errors[index])
+    }
+
     pub fn push(&mut self, triangle_index: usize, error: Error) {
         let queue_length = self.triangle_queue.len();
 
         if triangle_index >= self.triangle_queue_indices.len() {
-            self.triangle_queue_indices
-                .resize(self.triangle_queue_indices.len() * 2, None);
+            // initial size may be zero for tiny grids, so doubling alone is not enough
+            let new_length = (triangle_index + 1).max(self.triangle_queue_indices.len() * 2);
+            self.triangle_queue_indices.resize(new_length, None);
         }
         self.triangle_queue_indices[triangle_index] = Some(queue_length);
         self.triangle_queue.push(triangle_index);
@@ -47,8 +98,12 @@ impl PriorityQueue {
         self.up(queue_length);
     }
 
+    /// Pops triangle with the largest error, `None` when the queue is empty.
+    ///
+    /// With a single element `down(0, 0)` is a no-op, so the element is popped from the back
+    /// and its position in `triangle_queue_indices` is reset to `None`.
     pub fn pop(&mut self) -> Option<usize> {
-        let last_item_index = self.triangle_queue.len() - 1;
+        let last_item_index = self.triangle_queue.len().checked_sub(1)?;
         self.swap(0, last_item_index);
         self.down(0, last_item_index);
 
@@ -61,15 +116,7 @@ impl PriorityQueue {
             .get(requested_triangle_index)
             .and_then(|i| *i)
         else {
-            let pending_length = self.pending_triangle_indices.len();
-            if let Some(pos) = self
-                .pending_triangle_indices
-                .iter()
-                .position(|&triangle_index| triangle_index == requested_triangle_index)
-            {
-                self.pending_triangle_indices.swap(pos, pending_length - 1);
-                self.pending_triangle_indices.pop();
-            }
+            self.remove_pending(requested_triangle_index);
 
             return;
         };
@@ -84,6 +131,22 @@ impl PriorityQueue {
         self.pop_back();
     }
 
+    /// Removes the pending triangle by moving the last pending triangle into its place.
+    fn remove_pending(&mut self, triangle_index: usize) {
+        let Some(position) = self
+            .pending_triangle_positions
+            .get_mut(triangle_index)
+            .and_then(Option::take)
+        else {
+            return;
+        };
+
+        self.pending_triangle_indices.swap_remove(position);
+        if let Some(&moved_triangle_index) = self.pending_triangle_indices.get(position) {
+            self.pending_triangle_positions[moved_triangle_index] = Some(position);
+        }
+    }
+
     fn up(&mut self, mut j: usize) {
         if j == 0 {
             return;
@@ -150,3 +213,70 @@ impl PriorityQueue {
         triangle
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that every queued triangle is recorded at its position and no other is.
+    fn assert_indices_consistent(queue: &PriorityQueue) {
+        for (triangle_index, position) in queue.triangle_queue_indices.iter().enumerate() {
+            match position {
+                Some(position) => assert_eq!(queue.triangle_queue[*position], triangle_index),
+                None => assert!(!queue.triangle_queue.contains(&triangle_index)),
+            }
+        }
+    }
+
+    #[test]
+    fn pop_returns_triangles_by_descending_error() {
+        let errors = [4.0, 1.0, 7.0];
+        for length in 1..=errors.len() {
+            let mut queue = PriorityQueue::new(0);
+            for (triangle_index, &error) in errors[..length].iter().enumerate() {
+                queue.push(triangle_index, Error(error));
+                assert_indices_consistent(&queue);
+            }
+
+            let mut expected = (0..length).collect::<Vec<usize>>();
+            expected.sort_by(|&a, &b| errors[b].total_cmp(&errors[a]));
+            for &triangle_index in &expected {
+                assert_eq!(queue.get_max_error(), Some(&Error(errors[triangle_index])));
+                assert_eq!(queue.pop(), Some(triangle_index));
+                assert_eq!(queue.get_error(triangle_index), None);
+                assert_indices_consistent(&queue);
+            }
+            assert_eq!(queue.pop(), None);
+            assert!(queue.triangle_queue_indices.iter().all(Option::is_none));
+        }
+    }
+
+    #[test]
+    fn cleared_queue_is_like_new() {
+        let mut queue = PriorityQueue::new(4);
+        for (triangle_index, error) in [(0, 1.0), (3, 5.0), (6, 2.0)] {
+            queue.push(triangle_index, Error(error));
+        }
+        queue.add_pending_triangle(2);
+        queue.add_pending_triangle(9);
+        let index_capacity = queue.triangle_queue_indices.len();
+
+        queue.clear();
+
+        assert!(queue.triangle_queue.is_empty());
+        assert!(queue.triangle_errors.is_empty());
+        assert!(queue.pending_triangle_indices.is_empty());
+        assert_eq!(queue.get_max_error(), None);
+        assert_eq!(queue.triangle_queue_indices, vec![None; index_capacity]);
+        assert!(queue.pending_triangle_positions.iter().all(Option::is_none));
+        assert!(queue.consume_pending_triangles().is_empty());
+        assert_eq!(queue.pop(), None);
+
+        queue.push(3, Error(1.0));
+        queue.add_pending_triangle(2);
+        assert_eq!(queue.get_error(3), Some(Error(1.0)));
+        assert_eq!(queue.get_error(6), None);
+        assert_eq!(queue.consume_pending_triangles(), vec![2]);
+        assert_eq!(queue.pop(), Some(3));
+    }
+}
@@ -0,0 +1,81 @@
+/*!
+Ready-made reporters of triangulation progress.
+*/
+
+use std::io::{self, Write};
+
+use crate::Error;
+
+/// Writes progress of the triangulation as JSON lines, e.g. for piping into monitoring.
+///
+/// Every `interval` refinement steps one line is written:
+///
+/// ```text
+/// {"step":100,"vertices":104,"triangles":202,"max_error":1.25}
+/// ```
+///
+/// Max error which is not finite is written as `null`. Interval of `0` is treated as `1`.
+/// Writing errors don't interrupt the triangulation, the first one is kept and returned
+/// by [`JsonlReporter::finish`].
+#[derive(Debug)]
+pub struct JsonlReporter<W: Write> {
+    writer: W,
+    interval: usize,
+    step: usize,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonlReporter<W> {
+    /// Creates a reporter writing a line every `interval` refinement steps.
+    pub fn new(writer: W, interval: usize) -> Self {
+        Self {
+            writer,
+            interval: interval.max(1),
+            step: 0,
+            error: None,
+        }
+    }
+
+    /// Records one refinement step, writing a line if the interval has passed.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_error` - The maximum error of the triangulation after the step.
+    /// * `vertex_count` - Number of vertices after the step.
+    /// * `triangle_count` - Number of triangles after the step.
+    pub fn report(&mut self, max_error: Error, vertex_count: usize, triangle_count: usize) {
+        self.step += 1;
+        if self.error.is_some() || self.step % self.interval != 0 {
+            return;
+        }
+
+        let result = if max_error.0.is_finite() {
+            writeln!(
+                self.writer,
+                r#"{{"step":{},"vertices":{},"triangles":{},"max_error":{}}}"#,
+                self.step, vertex_count, triangle_count, max_error.0
+            )
+        } else {
+            writeln!(
+                self.writer,
+                r#"{{"step":{},"vertices":{},"triangles":{},"max_error":null}}"#,
+                self.step, vertex_count, triangle_count
+            )
+        };
+        self.error = result.err();
+    }
+
+    /// Flushes the writer and returns it.
+    ///
+    /// # Errors
+    ///
+    /// The first error which happened while writing or flushing.
+    pub fn finish(mut self) -> io::Result<W> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::HashSet;
+
+use crate::error::TriangulationError;
+use crate::triangulation::Triangulation;
+use crate::{
+    check_finite_heights, check_grid_size, Error, ErrorThreshold, Height, HeightValue, Mesh, Point,
+    Triangle,
+};
+
+/// Handle for refining a triangulation step by step, e.g. for view-dependent refinement.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{Error, Refiner};
+///
+/// let heights = (0..33 * 33)
+///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+///     .collect::<Vec<f64>>();
+///
+/// let mut refiner = Refiner::new(&heights, (33, 33))?;
+/// refiner.refine(Error(20.0))?;
+/// let coarse = refiner.mesh();
+///
+/// // continue from the coarse mesh, adding detail only to the top left quadrant
+/// let mut refiner = Refiner::from_mesh(&heights, (33, 33), &coarse)?;
+/// refiner.refine_region((0, 0, 16, 16), Error(0.5))?;
+/// let mesh = refiner.mesh();
+///
+/// assert!(mesh.points().len() > coarse.points().len());
+/// for point in &mesh.points()[coarse.points().len()..] {
+///     assert!(point.0 < 16 && point.1 < 16);
+/// }
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Refiner<'a, H = Height> {
+    triangulation: Triangulation<'a, H>,
+    width: usize,
+    height: usize,
+    /// Threshold [`Refiner::step`] refines towards, relative one is converted for every grid
+    threshold: ErrorThreshold,
+    /// Threshold in units of heights of the current grid
+    max_error: Error,
+}
+
+impl<'a, H: HeightValue> Refiner<'a, H> {
+    /// Creates a refiner with the initial two triangles covering the grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid.
+    /// * `dimesions` - Tuple width and height of the grid.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidDataLengthError` - If the length of the height data does not match the width and height of the grid.
+    /// - `GridTooSmall` - If the width or height of the grid is less than 2.
+    /// - `GridTooLarge` - If the grid has too many pixels for the `u32-indices` feature.
+    /// - `NonFiniteHeight` - If any height is `NaN` or infinite, with the index of the first one.
+    pub fn new(
+        height_data: &'a [H],
+        dimesions: (usize, usize),
+    ) -> Result<Self, TriangulationError> {
+        let (width, height) = dimesions;
+        check_grid_size(width, height)?;
+        (height_data.len() == width * height)
+            .then_some(())
+            .ok_or(TriangulationError::InvalidDataLengthError)?;
+        check_finite_heights(height_data, None)?;
+
+        let mut triangulation = Triangulation::new(height_data, width, height);
+        triangulation.initialize();
+
+        Ok(Self::from_triangulation(
+            triangulation,
+            (width, height),
+            Error(0.0).into(),
+        ))
+    }
+
+    /// Creates a refiner with storage presized for the expected number of vertices.
+    ///
+    /// Refinement doesn't reallocate until the mesh grows beyond `expected_vertices`,
+    /// which avoids latency spikes in interactive sessions that know their budget.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid.
+    /// * `dimesions` - Tuple width and height of the grid.
+    /// * `expected_vertices` - Number of vertices to presize storage for.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidDataLengthError` - If the length of the height data does not match the width and height of the grid.
+    /// - `GridTooSmall` - If the width or height of the grid is less than 2.
+    /// - `GridTooLarge` - If the grid has too many pixels for the `u32-indices` feature.
+    /// - `NonFiniteHeight` - If any height is `NaN` or infinite, with the index of the first one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, Refiner};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let mut refiner = Refiner::with_capacity(&heights, (33, 33), 500)?;
+    /// let capacity = refiner.vertex_capacity();
+    /// assert!(capacity >= 500);
+    ///
+    /// refiner.refine(Error(5.0))?;
+    /// assert!(refiner.mesh().points().len() <= 500);
+    /// assert_eq!(refiner.vertex_capacity(), capacity);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn with_capacity(
+        height_data: &'a [H],
+        dimesions: (usize, usize),
+        expected_vertices: usize,
+    ) -> Result<Self, TriangulationError> {
+        let (width, height) = dimesions;
+        check_grid_size(width, height)?;
+        (height_data.len() == width * height)
+            .then_some(())
+            .ok_or(TriangulationError::InvalidDataLengthError)?;
+        check_finite_heights(height_data, None)?;
+
+        let mut triangulation = Triangulation::new(height_data, width, height);
+        triangulation.reserve(expected_vertices);
+        triangulation.initialize();
+
+        Ok(Self::from_triangulation(
+            triangulation,
+            (width, height),
+            Error(0.0).into(),
+        ))
+    }
+
+    /// Creates a refiner continuing from the mesh, e.g. a coarse one loaded from disk.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the grid the mesh was built from.
+    /// * `dimesions` - Tuple width and height of the grid.
+    /// * `mesh` - Mesh to continue from.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidDataLengthError` - If the length of the height data does not match the width and height of the grid.
+    /// - `GridTooSmall` - If the width or height of the grid is less than 2.
+    /// - `GridTooLarge` - If the grid has too many pixels for the `u32-indices` feature.
+    /// - `NonFiniteHeight` - If any height is `NaN` or infinite, with the index of the first one.
+    /// - `InvalidMeshError` - If the mesh has points outside of the grid, duplicate points,
+    ///   invalid triangles or more triangles than a triangulation of its points can have.
+    pub fn from_mesh(
+        height_data: &'a [H],
+        dimesions: (usize, usize),
+        mesh: &Mesh,
+    ) -> Result<Self, TriangulationError> {
+        let (width, height) = dimesions;
+        check_grid_size(width, height)?;
+        (height_data.len() == width * height)
+            .then_some(())
+            .ok_or(TriangulationError::InvalidDataLengthError)?;
+        check_finite_heights(height_data, None)?;
+
+        let points = mesh.points();
+        let half_edges = mesh.half_edges();
+        let mut seen_points = HashSet::with_capacity(points.len());
+        // distinct points and at most two triangles per vertex, which refinement keeps, bound
+        // indices of the refined mesh by those of a mesh of every pixel, checked above
+        let is_valid = points
+            .iter()
+            .all(|&point| point.0 < width && point.1 < height && seen_points.insert(point))
+            && mesh.triangles_flat().len() <= 6 * points.len()
+            && mesh
+                .triangles_flat()
+                .iter()
+                .all(|&vertex| vertex < points.len())
+            && half_edges
+                .iter()
+                .all(|half_edge| half_edge.is_none_or(|half_edge| half_edge < half_edges.len()));
+        is_valid
+            .then_some(())
+            .ok_or(TriangulationError::InvalidMeshError)?;
+
+        Ok(Self::from_triangulation(
+            Triangulation::from_mesh(height_data, width, height, mesh),
+            (width, height),
+            Error(0.0).into(),
+        ))
+    }
+
+    /// Creates a refiner around an already configured triangulation.
+    pub(crate) fn from_triangulation(
+        triangulation: Triangulation<'a, H>,
+        dimesions: (usize, usize),
+        threshold: ErrorThreshold,
+    ) -> Self {
+        let (width, height) = dimesions;
+        let max_error = triangulation.absolute_error(threshold);
+        Self {
+            triangulation,
+            width,
+            height,
+            threshold,
+            max_error,
+        }
+    }
+
+    /// Refines the whole grid until the maximum error is below the specified threshold.
+    ///
+    /// # Errors
+    ///
+    /// - `MaxErrorRetrievalError` - If the maximum error is not found in the priority queue.
+    /// - `EmptyQueueError` - If the priority queue is empty during triangulation.
+    /// - `Cancelled` - If the cancellation token is cancelled, with the mesh refined so far.
+    pub fn refine(&mut self, max_error: Error) -> Result<(), TriangulationError> {
+        self.triangulation.set_region((
+            0,
+            0,
+            self.width.saturating_sub(1),
+            self.height.saturating_sub(1),
+        ));
+        self.triangulation.refine_until(max_error)
+    }
+
+    /// Continues refinement of the whole grid to a lower maximum error, e.g. when a finer
+    /// mesh is needed after a coarse one.
+    ///
+    /// Refinement only adds vertices, so continuing gives the same mesh as refining to the
+    /// lower error from the start, without redoing the coarse part.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_error` - The new maximum allowable error, not above the achieved one.
+    ///
+    /// # Errors
+    ///
+    /// - `MaxErrorIncreased` - If `max_error` is above the maximum error of the current mesh,
+    ///   which would need removing vertices.
+    /// - `MaxErrorRetrievalError` - If the maximum error is not found in the priority queue.
+    /// - `EmptyQueueError` - If the priority queue is empty during triangulation.
+    /// - `Cancelled` - If the cancellation token is cancelled, with the mesh refined so far.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate_mesh, Error, Refiner, TriangulationError};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    /// let mut refiner = Refiner::new(&heights, (33, 33))?;
+    /// refiner.refine(Error(4.0))?;
+    ///
+    /// refiner.refine_to(Error(2.0))?;
+    /// assert_eq!(refiner.mesh(), triangulate_mesh(&heights, (33, 33), Error(2.0))?);
+    ///
+    /// assert!(matches!(
+    ///     refiner.refine_to(Error(4.0)),
+    ///     Err(TriangulationError::MaxErrorIncreased { .. })
+    /// ));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn refine_to(&mut self, max_error: Error) -> Result<(), TriangulationError> {
+        self.triangulation.set_region((
+            0,
+            0,
+            self.width.saturating_sub(1),
+            self.height.saturating_sub(1),
+        ));
+        let achieved = self.triangulation.stats().final_max_error;
+        if max_error > achieved {
+            return Err(TriangulationError::MaxErrorIncreased {
+                requested: max_error,
+                achieved,
+            });
+        }
+
+        self.triangulation.refine_until(max_error)
+    }
+
+    /// Refines only inside the region until its maximum error is below the specified threshold.
+    ///
+    /// Only pixels inside the region are measured, so triangles entirely outside of it
+    /// have zero error and are not refined. Triangles straddling the region boundary are
+    /// refined by their part inside the region, new vertices are always inside the region.
+    ///
+    /// # Arguments
+    ///
+    /// * `region` - Min x, min y, max x, max y of the region, max values are exclusive.
+    /// * `max_error` - The maximum allowable error inside the region.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidRegionError` - If the region is empty or doesn't fit the grid.
+    /// - Same as [`Refiner::refine`] otherwise.
+    pub fn refine_region(
+        &mut self,
+        region: (usize, usize, usize, usize),
+        max_error: Error,
+    ) -> Result<(), TriangulationError> {
+        let (min_x, min_y, max_x, max_y) = region;
+        (min_x < max_x && min_y < max_y && max_x <= self.width && max_y <= self.height)
+            .then_some(())
+            .ok_or(TriangulationError::InvalidRegionError)?;
+
+        self.triangulation
+            .set_region((min_x, min_y, max_x - 1, max_y - 1));
+        self.triangulation.refine_until(max_error)
+    }
+
+    /// Inserts a single vertex if the maximum error is above the stored threshold.
+    ///
+    /// Threshold is [`TriangulationOptions::max_error`](crate::TriangulationOptions::max_error)
+    /// for refiners created by [`TriangulationOptions::start`](crate::TriangulationOptions::start)
+    /// and zero otherwise. [`RelativeError`](crate::RelativeError) is converted with
+    /// the elevation range of the grid, again after every [`Refiner::reset`]. Only the region
+    /// of the last [`Refiner::refine_region`] is refined, the whole grid if there was none.
+    /// The mesh is consistent between steps, so it can be inspected with [`Refiner::snapshot`]
+    /// or [`Refiner::mesh`] at any time.
+    ///
+    /// # Returns
+    ///
+    /// Whether the maximum error is still above the threshold and no size limit is reached,
+    /// so `while refiner.step()? {}` refines until done.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Refiner::refine`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, Error, TriangulationOptions};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    ///
+    /// let mut refiner = TriangulationOptions::new()
+    ///     .max_error(Error(1.0))
+    ///     .start(&heights, 33, 33)?;
+    /// let mut frames = vec![refiner.snapshot()];
+    /// while refiner.step()? {
+    ///     frames.push(refiner.snapshot());
+    /// }
+    /// frames.push(refiner.snapshot());
+    ///
+    /// assert!(frames.windows(2).all(|frames| frames[0].0.len() <= frames[1].0.len()));
+    /// assert_eq!(frames.last(), Some(&triangulate(&heights, (33, 33), Error(1.0))?));
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn step(&mut self) -> Result<bool, TriangulationError> {
+        self.triangulation.refine_step(self.max_error)
+    }
+
+    /// Current points and triangles of the triangulation, same as returned by [`triangulate`](crate::triangulate).
+    pub fn snapshot(&self) -> (Vec<Point>, Vec<Triangle>) {
+        (
+            self.triangulation.get_vertext_points(),
+            self.triangulation.get_triangle_indices(),
+        )
+    }
+
+    /// Starts over on new height data of the same size, e.g. the next tile of a tiled pipeline.
+    ///
+    /// Allocated memory and options are kept, so triangulating many tiles with one refiner
+    /// doesn't allocate once it has grown to the largest mesh. The refiner is left with
+    /// the initial two triangles, same as a new one, and with the seed points and breaklines
+    /// of its options. Local extrema of
+    /// [`TriangulationOptions::preserve_extrema`](crate::TriangulationOptions::preserve_extrema)
+    /// are found again in the new grid.
+    ///
+    /// # Arguments
+    ///
+    /// * `height_data` - Height values of the new grid.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidDataLengthError` - If the length of the height data does not match the width and height of the grid.
+    /// - `NonFiniteHeight` - If any height is `NaN` or infinite, with the index of the first one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{triangulate, Error, Refiner};
+    ///
+    /// let tiles = (1..4)
+    ///     .map(|frequency| {
+    ///         (0..33 * 33)
+    ///             .map(|i| ((i % 33 * frequency) as f64 / 9.0).sin() * 50.0)
+    ///             .collect::<Vec<f64>>()
+    ///     })
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let mut refiner = Refiner::new(&tiles[0], (33, 33))?;
+    /// for tile in &tiles {
+    ///     refiner.reset(tile)?;
+    ///     refiner.refine(Error(1.0))?;
+    ///     assert_eq!(refiner.snapshot(), triangulate(tile, (33, 33), Error(1.0))?);
+    /// }
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn reset(&mut self, height_data: &'a [H]) -> Result<(), TriangulationError> {
+        (height_data.len() == self.width * self.height)
+            .then_some(())
+            .ok_or(TriangulationError::InvalidDataLengthError)?;
+        check_finite_heights(height_data, self.triangulation.nodata())?;
+
+        self.triangulation.reset(height_data);
+        self.triangulation.initialize();
+        self.max_error = self.triangulation.absolute_error(self.threshold);
+
+        Ok(())
+    }
+
+    /// Number of vertices the refiner can hold without reallocating.
+    pub fn vertex_capacity(&self) -> usize {
+        self.triangulation.vertex_capacity()
+    }
+
+    /// Current state of the triangulation.
+    pub fn mesh(&self) -> Mesh {
+        self.triangulation.mesh()
+    }
+
+    /// Candidate point of every triangle of the current mesh with its error, e.g. for
+    /// visualizing where refinement goes next.
+    ///
+    /// Candidate is the pixel with the largest error inside the triangle, the one inserted
+    /// when the triangle is split. Candidates are kept up to date between refinement steps,
+    /// so this only copies them.
+    ///
+    /// # Returns
+    ///
+    /// Vector of candidate points and their errors, in the order of triangles of [`Refiner::mesh`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use delatin::{Error, Refiner};
+    ///
+    /// let heights = (0..33 * 33)
+    ///     .map(|i| ((i % 33) as f64 / 3.0).sin() * ((i / 33) as f64 / 3.0).cos() * 50.0)
+    ///     .collect::<Vec<f64>>();
+    /// let mut refiner = Refiner::new(&heights, (33, 33))?;
+    /// refiner.refine(Error(5.0))?;
+    ///
+    /// let candidates = refiner.candidates();
+    /// let mesh = refiner.mesh();
+    /// assert_eq!(candidates.len(), mesh.triangles().len());
+    /// // refinement stopped, so every triangle is within the max error
+    /// let worst = candidates.iter().map(|&(_, error)| error.0).fold(0.0, f64::max);
+    /// assert_eq!(worst, mesh.max_error().0);
+    /// assert!(worst <= 5.0);
+    /// # Ok::<(), delatin::TriangulationError>(())
+    /// ```
+    pub fn candidates(&self) -> Vec<(Point, Error)> {
+        self.triangulation.candidates()
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{Point, Triangle};
+
+/// Receiver of the triangulation results, e.g. for writing a large mesh straight to a file
+/// without returning vectors of points and triangles.
+///
+/// All points are passed first in the order of their indices, then all triangles. Refinement
+/// overwrites triangles in place, so results are passed once it's finished.
+///
+/// Vectors of points and triangles are sinks collecting them, the same as returned by
+/// [`triangulate`](crate::triangulate).
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::{triangulate_streaming, Error, MeshSink};
+///
+/// struct ObjWriter(String);
+///
+/// impl MeshSink for ObjWriter {
+///     fn on_point(&mut self, (x, y): (usize, usize)) {
+///         self.0.push_str(&format!("v {x} {y} 0\n"));
+///     }
+///
+///     fn on_triangle(&mut self, (a, b, c): (usize, usize, usize)) {
+///         self.0.push_str(&format!("f {} {} {}\n", a + 1, b + 1, c + 1));
+///     }
+/// }
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let mut obj = ObjWriter(String::new());
+/// triangulate_streaming(&heights, (2, 2), Error(1.0), &mut obj)?;
+/// assert_eq!(obj.0.lines().count(), 4 + 2);
+/// # Ok::<(), delatin::TriangulationError>(())
+/// ```
+pub trait MeshSink {
+    /// Receives a vertex of the mesh.
+    fn on_point(&mut self, point: Point);
+
+    /// Receives a triangle of the mesh, defined by indices of the points already received.
+    fn on_triangle(&mut self, triangle: Triangle);
+}
+
+impl MeshSink for (Vec<Point>, Vec<Triangle>) {
+    fn on_point(&mut self, point: Point) {
+        self.0.push(point);
+    }
+
+    fn on_triangle(&mut self, triangle: Triangle) {
+        self.1.push(triangle);
+    }
+}
@@ -0,0 +1,14 @@
+use crate::Error;
+
+/// Summary of the triangulation process, e.g. for tuning the maximum error empirically.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TriangulationStats {
+    /// The maximum error of the mesh when refinement stopped.
+    pub final_max_error: Error,
+    /// Number of refinement steps, each inserting one vertex.
+    pub iterations: usize,
+    /// Number of vertices of the mesh.
+    pub point_count: usize,
+    /// Number of triangles of the mesh.
+    pub triangle_count: usize,
+}
@@ -0,0 +1,59 @@
+use crate::Error;
+
+/// Error as a fraction of the elevation range (max - min) of the grid.
+///
+/// `RelativeError(0.01)` means 1% of the range, so the same value gives comparable meshes
+/// for grids ranging 0-10 and 0-8000. Flat grids have zero range and keep the initial
+/// two triangles.
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RelativeError(pub f64);
+
+/// The maximum allowable error, either absolute or relative to the elevation range.
+///
+/// Both [`Error`] and [`RelativeError`] convert into it, so they can be passed directly
+/// to [`TriangulationOptions::max_error`](crate::TriangulationOptions::max_error).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ErrorThreshold {
+    /// Error in units of heights.
+    Absolute(Error),
+    /// Error as a fraction of the elevation range, converted to absolute before refinement.
+    Relative(RelativeError),
+}
+
+impl Default for ErrorThreshold {
+    fn default() -> Self {
+        ErrorThreshold::Absolute(Error::default())
+    }
+}
+
+impl From<Error> for ErrorThreshold {
+    fn from(error: Error) -> Self {
+        ErrorThreshold::Absolute(error)
+    }
+}
+
+impl From<RelativeError> for ErrorThreshold {
+    fn from(error: RelativeError) -> Self {
+        ErrorThreshold::Relative(error)
+    }
+}
+
+/// Condition at which refinement stops, size limits of
+/// [`TriangulationOptions`](crate::TriangulationOptions) apply with any of them.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StopCriterion {
+    /// Stop once the maximum error of any pixel is below
+    /// [`TriangulationOptions::max_error`](crate::TriangulationOptions::max_error).
+    #[default]
+    MaxError,
+    /// Stop once the root-mean-square error over all grid samples is at most the value.
+    ///
+    /// Single outliers don't keep the refinement going, so the mesh fits the surface more
+    /// evenly. Squared errors are summed while rasterizing triangles, which makes every
+    /// refinement step somewhat more expensive than in max error mode. The triangle with
+    /// the maximum error is still refined first.
+    Rmse(f64),
+}
@@ -1,26 +1,121 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
 use crate::error::TriangulationError;
 use crate::priority_queue::PriorityQueue;
-use crate::utils::{get_signed_area, is_point_in_circumcircle};
-use crate::{Error, Height, Point, Triangle};
+use crate::utils::{
+    circumcircle_determinant, find_local_extrema, get_signed_area, is_nodata_value,
+    is_point_in_circumcircle, is_point_in_scaled_circumcircle, orientation,
+    scaled_circumcircle_determinant, tie_break_hash,
+};
+use crate::{
+    AbsoluteError, CancellationToken, Error, ErrorMetric, ErrorThreshold, Height, HeightValue,
+    Mesh, MeshSink, Point, PointWithHeight, RelativeError, Triangle, TriangulationStats,
+};
 
 #[derive(Debug, Clone)]
-pub(crate) struct Triangulation<'a> {
+pub(crate) struct Triangulation<'a, H = Height, M = AbsoluteError> {
     /// Height data of the grid
-    height_data: &'a [Height],
+    height_data: &'a [H],
     /// Width of the grid
     width: usize,
     /// Height of the grid
     height: usize,
+    /// Distance between starts of rows in `height_data`, wider than the grid for a region
+    /// of a larger grid
+    stride: usize,
+    /// Offset added to output points, e.g. for regions output in coordinates of the full grid
+    output_offset: Point,
     /// Points of vertices
     vertex_points: Vec<Point>,
+    /// Vertex of each point, built on the first insertion of a user point and kept up to date
+    vertex_lookup: Option<HashMap<Point, usize>>,
+    /// Triangle where the last user point was inserted, the walk to the next one starts there
+    walk_start: usize,
     /// A vector of vertex points indices, triplets of these indices form triangles.
-    triangles: Vec<usize>,
-    /// A vector halfedge indices
-    half_edges: Vec<Option<usize>>,
+    triangles: Vec<Index>,
+    /// A vector halfedge indices, `NO_HALF_EDGE` for edges without adjacent triangle
+    half_edges: Vec<Index>,
     /// A vector of candidate points
     candidate_points: Vec<Point>,
     // Priority queue for triangles
     priority_queue: PriorityQueue,
+    /// Optional confidence of each height value, errors are multiplied by it
+    confidence: Option<&'a [f64]>,
+    /// Points inserted as vertices before refinement
+    seed_points: Vec<Point>,
+    /// Segments which are inserted as chains of edges before refinement
+    breaklines: Vec<(Point, Point)>,
+    /// Whether local extrema of the grid are inserted as vertices before refinement
+    preserve_extrema: bool,
+    /// Edges between these vertex indices (smaller first) are never flipped by `legalize`
+    constrained_edges: HashSet<(usize, usize)>,
+    /// Optional seed for breaking ties between candidate points with equal error
+    jitter_seed: Option<u64>,
+    /// Optional maximum length of triangle edges
+    max_edge_length: Option<f64>,
+    /// Optional ground size of a pixel in x and y, for anisotropic grids
+    cell_size: Option<(f64, f64)>,
+    /// Optional margin of the circumcircle determinant below which edges are not flipped
+    circumcircle_tolerance: Option<f64>,
+    /// Optional vertex count at which refinement stops
+    max_points: Option<usize>,
+    /// Optional triangle count at which refinement stops
+    max_triangles: Option<usize>,
+    /// Number of refinement steps done
+    iterations: usize,
+    /// Waterline below which heights are treated as flat for the error metric
+    clip_below: Option<f64>,
+    /// Sentinel height of void pixels, which are skipped when measuring error
+    nodata: Option<f64>,
+    /// Whether valid pixels are refined until triangles of valid vertices cover them
+    cover_valid_pixels: bool,
+    /// Optional root-mean-square error at which refinement stops instead of the max error
+    rmse_target: Option<f64>,
+    /// Sums of squared pixel errors of triangles, tracked only with `rmse_target`
+    squared_errors: Vec<f64>,
+    /// Sum of finite `squared_errors`
+    squared_error_sum: f64,
+    /// Number of triangles with infinite squared error, e.g. without valid vertices
+    infinite_error_count: usize,
+    /// Number of pixels which are not void, the RMSE is averaged over
+    valid_pixel_count: usize,
+    /// Optional flag checked before every refinement step
+    cancellation: Option<CancellationToken>,
+    /// Whether output points are flipped to y growing upwards, with reversed triangles
+    flip_y: bool,
+    /// Inclusive min x, min y, max x, max y of the area where candidate points are searched
+    region: (usize, usize, usize, usize),
+    /// Error of a pixel between its predicted and actual height
+    metric: M,
+}
+
+/// Integer type of indices stored in `triangles` and `half_edges`.
+/// `u32-indices` feature halves memory used by them, but limits grids to `MAX_PIXELS` pixels.
+#[cfg(feature = "u32-indices")]
+type Index = u32;
+#[cfg(not(feature = "u32-indices"))]
+type Index = usize;
+
+/// Stored in `half_edges` instead of `Option` to keep them the size of `Index`.
+const NO_HALF_EDGE: Index = Index::MAX;
+
+/// Largest number of pixels of a grid whose meshes have all indices below `NO_HALF_EDGE`.
+/// Mesh of `n` vertices has less than `2 * n` triangles, so less than `6 * n` half-edges.
+#[allow(clippy::unnecessary_cast)] // `Index` is `usize` without `u32-indices`
+pub(crate) const MAX_PIXELS: usize = NO_HALF_EDGE as usize / 6;
+
+/// Key of the edge in `constrained_edges`, independent of its direction.
+fn edge_key(vertex_a: usize, vertex_b: usize) -> (usize, usize) {
+    (vertex_a.min(vertex_b), vertex_a.max(vertex_b))
+}
+
+/// Point with the maximum error of a rasterized triangle.
+struct Candidate {
+    point: Point,
+    max_error: Error,
+    /// Sum of squared errors of pixels owned by the triangle, zero unless RMSE is tracked
+    squared_error: f64,
 }
 
 enum AddTriangleStrategy {
@@ -28,7 +123,7 @@ enum AddTriangleStrategy {
     Update(usize),
 }
 
-impl<'a> Triangulation<'a> {
+impl<'a, H: HeightValue> Triangulation<'a, H> {
     /// Creates a new instance of `Triangulation` with the given height data, width, and height.
     ///
     /// # Arguments
@@ -40,42 +135,331 @@ impl<'a> Triangulation<'a> {
     /// # Returns
     ///
     /// A new `Triangulation` instance.
-    pub(crate) fn new(height_data: &'a [Height], width: usize, height: usize) -> Self {
+    pub(crate) fn new(height_data: &'a [H], width: usize, height: usize) -> Self {
+        Self::with_metric(height_data, width, height, AbsoluteError)
+    }
+
+    /// Creates a new instance of `Triangulation` continuing from the existing mesh.
+    pub(crate) fn from_mesh(
+        height_data: &'a [H],
+        width: usize,
+        height: usize,
+        mesh: &Mesh,
+    ) -> Self {
+        let mut triangulation = Self::new(height_data, width, height);
+        triangulation.vertex_points = mesh.points().to_vec();
+        for (edge, &vertex) in mesh.triangles_flat().iter().enumerate() {
+            triangulation.triangles.push(to_index(vertex));
+            triangulation
+                .half_edges
+                .push(to_stored_half_edge(mesh.half_edges()[edge]));
+        }
+        for triangle_index in 0..triangulation.triangles.len() / 3 {
+            triangulation.candidate_points.push((0, 0));
+            triangulation
+                .priority_queue
+                .add_pending_triangle(triangle_index);
+        }
+        triangulation.flush();
+
+        triangulation
+    }
+}
+
+impl<'a, H: HeightValue, M: ErrorMetric> Triangulation<'a, H, M> {
+    /// Same as [`Triangulation::new`], measuring errors of pixels with the metric.
+    pub(crate) fn with_metric(
+        height_data: &'a [H],
+        width: usize,
+        height: usize,
+        metric: M,
+    ) -> Self {
         let initial_queue_size = width * height / 4;
         Self {
             height_data,
             width,
             height,
+            stride: width,
+            output_offset: (0, 0),
             vertex_points: Vec::default(),
+            vertex_lookup: None,
+            walk_start: 0,
             triangles: Vec::default(),
             half_edges: Vec::default(),
             candidate_points: Vec::default(),
             priority_queue: PriorityQueue::new(initial_queue_size),
+            confidence: None,
+            seed_points: Vec::default(),
+            breaklines: Vec::default(),
+            preserve_extrema: false,
+            constrained_edges: HashSet::default(),
+            jitter_seed: None,
+            max_edge_length: None,
+            cell_size: None,
+            circumcircle_tolerance: None,
+            clip_below: None,
+            nodata: None,
+            cover_valid_pixels: false,
+            rmse_target: None,
+            squared_errors: Vec::default(),
+            squared_error_sum: 0.0,
+            infinite_error_count: 0,
+            valid_pixel_count: width * height,
+            max_points: None,
+            max_triangles: None,
+            iterations: 0,
+            cancellation: None,
+            flip_y: false,
+            region: (0, 0, width.saturating_sub(1), height.saturating_sub(1)),
+            metric,
         }
     }
 
-    /// Runs the triangulation process until the maximum error is below the specified threshold.
+    /// Presizes internal storage for the expected number of vertices.
+    ///
+    /// Triangulation of `n` vertices has less than `2 * n` triangles, each with three
+    /// vertex indices, three half-edges and one candidate point.
+    pub(crate) fn reserve(&mut self, expected_vertices: usize) {
+        let triangle_count = 2 * expected_vertices;
+        self.vertex_points
+            .reserve(expected_vertices.saturating_sub(self.vertex_points.len()));
+        self.triangles
+            .reserve((3 * triangle_count).saturating_sub(self.triangles.len()));
+        self.half_edges
+            .reserve((3 * triangle_count).saturating_sub(self.half_edges.len()));
+        self.candidate_points
+            .reserve(triangle_count.saturating_sub(self.candidate_points.len()));
+        self.priority_queue.reserve(triangle_count);
+    }
+
+    /// Rebinds the triangulation to new height data of the same size and empties it,
+    /// keeping allocated memory and options. Needs [`Triangulation::initialize`] afterwards.
+    pub(crate) fn reset(&mut self, height_data: &'a [H]) {
+        self.height_data = height_data;
+        self.vertex_points.clear();
+        self.vertex_lookup = None;
+        self.walk_start = 0;
+        self.triangles.clear();
+        self.half_edges.clear();
+        self.candidate_points.clear();
+        self.priority_queue.clear();
+        self.constrained_edges.clear();
+        self.squared_errors.clear();
+        self.squared_error_sum = 0.0;
+        self.infinite_error_count = 0;
+        self.valid_pixel_count = self.count_valid_pixels();
+        self.iterations = 0;
+        self.region = (
+            0,
+            0,
+            self.width.saturating_sub(1),
+            self.height.saturating_sub(1),
+        );
+    }
+
+    /// Difference between the highest and the lowest height as measured for the error,
+    /// void pixels are skipped. Zero for flat or entirely void grids.
+    pub(crate) fn elevation_range(&self) -> f64 {
+        let mut range: Option<(f64, f64)> = None;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.is_nodata((x, y)) {
+                    continue;
+                }
+
+                let height = self.height_at((x, y));
+                range = Some(range.map_or((height, height), |(min, max)| {
+                    (min.min(height), max.max(height))
+                }));
+            }
+        }
+
+        range.map_or(0.0, |(min, max)| max - min)
+    }
+
+    /// Threshold in units of heights, relative thresholds are scaled by the elevation range.
+    pub(crate) fn absolute_error(&self, threshold: ErrorThreshold) -> Error {
+        match threshold {
+            ErrorThreshold::Absolute(max_error) => max_error,
+            ErrorThreshold::Relative(RelativeError(fraction)) => {
+                Error(fraction * self.elevation_range())
+            }
+        }
+    }
+
+    /// Sentinel height of void pixels, if any.
+    pub(crate) fn nodata(&self) -> Option<f64> {
+        self.nodata
+    }
+
+    /// Number of vertices the triangulation can hold without reallocating.
+    pub(crate) fn vertex_capacity(&self) -> usize {
+        self.vertex_points.capacity()
+    }
+
+    /// Limits search of candidate points to the region, so only triangles overlapping it
+    /// get refined. Errors of all triangles are computed again.
     ///
     /// # Arguments
     ///
-    /// * `max_error` - The maximum allowable error for the triangulation process.
+    /// * `region` - Inclusive min x, min y, max x, max y of the region.
+    pub(crate) fn set_region(&mut self, region: (usize, usize, usize, usize)) {
+        if self.region == region {
+            return;
+        }
+
+        self.region = region;
+        self.priority_queue.clear();
+        for triangle_index in 0..self.triangles.len() / 3 {
+            self.priority_queue.add_pending_triangle(triangle_index);
+        }
+        self.flush();
+    }
+
+    /// Makes refinement stop once the triangulation has `max_points` vertices.
+    pub(crate) fn set_max_points(&mut self, max_points: usize) {
+        self.max_points = Some(max_points);
+    }
+
+    /// Makes refinement stop once the triangulation has at least `max_triangles` triangles.
+    pub(crate) fn set_max_triangles(&mut self, max_triangles: usize) {
+        self.max_triangles = Some(max_triangles);
+    }
+
+    /// Makes heights below `clip_below` treated as equal to it when measuring error.
+    pub(crate) fn set_clip_below(&mut self, clip_below: f64) {
+        self.clip_below = Some(clip_below);
+    }
+
+    /// Makes pixels with the `nodata` height skipped when measuring error.
+    pub(crate) fn set_nodata(&mut self, nodata: f64) {
+        self.nodata = Some(nodata);
+    }
+
+    /// Makes valid pixels in triangles with void vertices refined regardless of their error,
+    /// unless they lie on an edge between valid vertices, so dropping triangles with void
+    /// vertices afterwards leaves every valid pixel covered.
+    pub(crate) fn set_cover_valid_pixels(&mut self, cover_valid_pixels: bool) {
+        self.cover_valid_pixels = cover_valid_pixels;
+    }
+
+    /// Makes refinement stop once the root-mean-square error over valid pixels is at most
+    /// `rmse_target`, instead of comparing the maximum error.
+    pub(crate) fn set_rmse_target(&mut self, rmse_target: f64) {
+        self.rmse_target = Some(rmse_target);
+        self.valid_pixel_count = self.count_valid_pixels();
+    }
+
+    /// Root-mean-square error over valid pixels, as tracked with `rmse_target`.
+    fn rmse(&self) -> f64 {
+        if self.infinite_error_count > 0 {
+            return f64::INFINITY;
+        }
+        if self.valid_pixel_count == 0 {
+            return 0.0;
+        }
+
+        // running sum may drift slightly below zero
+        (self.squared_error_sum.max(0.0) / self.valid_pixel_count as f64).sqrt()
+    }
+
+    fn count_valid_pixels(&self) -> usize {
+        match self.nodata {
+            Some(_) => (0..self.height)
+                .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+                .filter(|&point| !self.is_nodata(point))
+                .count(),
+            None => self.width * self.height,
+        }
+    }
+
+    /// Makes heights looked up in rows `stride` apart, for triangulating a region of a larger
+    /// grid in place. `height_data` has to start at the top left pixel of the region.
+    pub(crate) fn set_stride(&mut self, stride: usize) {
+        self.stride = stride;
+    }
+
+    /// Makes output points shifted by the offset, applied after flipping y.
+    pub(crate) fn set_output_offset(&mut self, output_offset: Point) {
+        self.output_offset = output_offset;
+    }
+
+    /// Makes triangles with an edge longer than `max_edge_length` refined regardless of their error.
+    pub(crate) fn set_max_edge_length(&mut self, max_edge_length: f64) {
+        self.max_edge_length = Some(max_edge_length);
+    }
+
+    /// Makes Delaunay test and edge lengths computed in world units of pixels sized `cell_size`.
+    pub(crate) fn set_cell_size(&mut self, cell_size: (f64, f64)) {
+        self.cell_size = Some(cell_size);
+    }
+
+    /// Makes edges flipped only when the circumcircle determinant is below `-tolerance`.
+    pub(crate) fn set_circumcircle_tolerance(&mut self, tolerance: f64) {
+        self.circumcircle_tolerance = Some(tolerance);
+    }
+
+    /// Makes candidate points with equal error chosen pseudo-randomly based on the seed
+    /// instead of the first one in scan order.
+    pub(crate) fn set_jitter_seed(&mut self, seed: u64) {
+        self.jitter_seed = Some(seed);
+    }
+
+    /// Sets confidence of each height value, so unreliable samples don't drive refinement.
     ///
-    /// # Returns
+    /// # Arguments
+    ///
+    /// * `confidence` - Weights with the same layout as height data, error of each sample is multiplied by its weight.
+    pub(crate) fn set_confidence(&mut self, confidence: &'a [f64]) {
+        self.confidence = Some(confidence);
+    }
+
+    /// Makes refinement stop with `Cancelled` error once the token is cancelled.
+    pub(crate) fn set_cancellation(&mut self, cancellation: CancellationToken) {
+        self.cancellation = Some(cancellation);
+    }
+
+    /// Makes output points flipped to `(x, height - 1 - y)` with triangles in reversed order.
+    /// Only the output is flipped, refinement works on the grid as it is.
+    pub(crate) fn set_flip_y(&mut self, flip_y: bool) {
+        self.flip_y = flip_y;
+    }
+
+    /// Adds points which will be vertices of the mesh regardless of their error.
+    pub(crate) fn add_seed_points(&mut self, seed_points: &[Point]) {
+        self.seed_points.extend_from_slice(seed_points);
+    }
+
+    /// Adds segments which will be followed by edges of the mesh, which are never flipped.
+    pub(crate) fn add_breaklines(&mut self, breaklines: &[(Point, Point)]) {
+        self.breaklines.extend_from_slice(breaklines);
+    }
+
+    /// Makes local extrema of the grid vertices of the mesh, found again for every grid
+    /// after [`Triangulation::reset`].
+    pub(crate) fn set_preserve_extrema(&mut self, preserve_extrema: bool) {
+        self.preserve_extrema = preserve_extrema;
+    }
+
+    /// Runs the triangulation process until the maximum error is below the specified threshold.
     ///
-    /// Tuple containing:
-    /// - points `Vec<(usize, usize)>`: A vector containing all the vertices of the triangulated mesh. Each point corresponds to heights vector index.
-    /// - triangles `Vec<(usize, usize, usize)>`: A vector containing all the triangles of the mesh, each defined by indices into the `points`.
+    /// # Arguments
+    ///
+    /// * `max_error` - The maximum allowable error for the triangulation process.
     ///
     /// # Errors
     ///
-    /// - `InvalidDataLengthError` - If the length of the height data does not match the width and height of the grid.
     /// - `MaxErrorRetrievalError` - If the maximum error is not found in the priority queue.
     /// - `EmptyQueueError` - If the priority queue is empty during triangulation.
     ///
-    pub(crate) fn run(
-        &mut self,
-        max_error: Error,
-    ) -> Result<(Vec<Point>, Vec<Triangle>), TriangulationError> {
+    pub(crate) fn run(&mut self, max_error: Error) -> Result<(), TriangulationError> {
+        self.initialize();
+        self.refine_until(max_error)
+    }
+
+    /// Adds grid corners with two initial triangles, seed points, local extrema and breaklines.
+    /// Seed points and breaklines are kept, so they are inserted again after a reset.
+    pub(crate) fn initialize(&mut self) {
         let initial_x = self.width - 1;
         let initial_y = self.height - 1;
 
@@ -109,88 +493,465 @@ impl<'a> Triangulation<'a> {
         );
         self.flush();
 
-        while self
+        for index in 0..self.seed_points.len() {
+            self.insert_point(self.seed_points[index]);
+        }
+        if self.preserve_extrema {
+            let extrema =
+                find_local_extrema(self.height_data, self.width, self.height, self.stride);
+            for point in extrema {
+                self.insert_point(point);
+            }
+        }
+        for index in 0..self.breaklines.len() {
+            let (start, end) = self.breaklines[index];
+            self.insert_breakline(start, end);
+        }
+    }
+
+    /// Refines the triangulation until the maximum error is below the specified threshold.
+    pub(crate) fn refine_until(&mut self, max_error: Error) -> Result<(), TriangulationError> {
+        self.refine_until_with_progress(max_error, |_, _, _| {})
+    }
+
+    /// Same as [`Triangulation::refine_until`], calling `progress` after every refinement step
+    /// with the current max error, vertex count and triangle count.
+    pub(crate) fn refine_until_with_progress(
+        &mut self,
+        max_error: Error,
+        mut progress: impl FnMut(Error, usize, usize),
+    ) -> Result<(), TriangulationError> {
+        while self.needs_refinement(max_error)? {
+            self.check_cancellation()?;
+            self.refine()?;
+            progress(
+                self.priority_queue
+                    .get_max_error()
+                    .copied()
+                    .unwrap_or_default(),
+                self.vertex_points.len(),
+                self.triangles.len() / 3,
+            );
+        }
+        self.log_completion();
+
+        Ok(())
+    }
+
+    /// Does one refinement step if the maximum error is above the threshold.
+    ///
+    /// Returns whether the maximum error is still above the threshold afterwards.
+    pub(crate) fn refine_step(&mut self, max_error: Error) -> Result<bool, TriangulationError> {
+        if !self.needs_refinement(max_error)? {
+            return Ok(false);
+        }
+        self.check_cancellation()?;
+        self.refine()?;
+
+        let needs_refinement = self.needs_refinement(max_error)?;
+        if !needs_refinement {
+            self.log_completion();
+        }
+
+        Ok(needs_refinement)
+    }
+
+    /// Returns `Cancelled` error with the mesh refined so far once the token is cancelled.
+    fn check_cancellation(&self) -> Result<(), TriangulationError> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(TriangulationError::Cancelled {
+                partial: Box::new(self.mesh()),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Logs the number of refinement steps and the final max error with the `log` feature.
+    fn log_completion(&self) {
+        #[cfg(feature = "log")]
+        log::debug!(
+            "Refinement done after {} iterations with max error {}",
+            self.iterations,
+            self.priority_queue
+                .get_max_error()
+                .copied()
+                .unwrap_or_default()
+        );
+    }
+
+    /// Checks whether the maximum error is above the threshold and no size limit is reached.
+    fn needs_refinement(&self, max_error: Error) -> Result<bool, TriangulationError> {
+        let current_max_error = self
             .priority_queue
             .get_max_error()
-            .ok_or(TriangulationError::MaxErrorRetrievalError)?
-            > &max_error
-        {
-            self.refine()?;
+            .ok_or(TriangulationError::MaxErrorRetrievalError)?;
+
+        let exceeds_threshold = match self.rmse_target {
+            // zero max error means the mesh is exact, so RMSE can't go any lower
+            Some(rmse_target) => current_max_error.0 > 0.0 && self.rmse() > rmse_target,
+            None => current_max_error > &max_error,
+        };
+
+        Ok(exceeds_threshold && !self.is_budget_exhausted())
+    }
+
+    /// Checks whether refinement reached one of the size limits.
+    ///
+    /// Checked only between steps, so the mesh is always consistent when refinement stops.
+    fn is_budget_exhausted(&self) -> bool {
+        self.max_points
+            .is_some_and(|max_points| self.vertex_points.len() >= max_points)
+            || self
+                .max_triangles
+                .is_some_and(|max_triangles| self.triangles.len() / 3 >= max_triangles)
+    }
+
+    /// Inserts a vertex in the center of every `block_size` x `block_size` block of the grid
+    /// which has no vertices. Blocks at the right and bottom edges may be smaller.
+    pub(crate) fn fill_empty_blocks(&mut self, block_size: usize) {
+        if block_size == 0 {
+            return;
+        }
+
+        let blocks_x = self.width.div_ceil(block_size);
+        let blocks_y = self.height.div_ceil(block_size);
+        let mut is_block_filled = vec![false; blocks_x * blocks_y];
+        for point in &self.vertex_points {
+            is_block_filled[point.1 / block_size * blocks_x + point.0 / block_size] = true;
+        }
+
+        for block_y in 0..blocks_y {
+            for block_x in 0..blocks_x {
+                if is_block_filled[block_y * blocks_x + block_x] {
+                    continue;
+                }
+
+                let min_x = block_x * block_size;
+                let min_y = block_y * block_size;
+                let max_x = (min_x + block_size).min(self.width) - 1;
+                let max_y = (min_y + block_size).min(self.height) - 1;
+                self.insert_point(((min_x + max_x) / 2, (min_y + max_y) / 2));
+            }
+        }
+    }
+
+    /// Summary of the refinement done so far, with the largest error left in the queue
+    /// (zero when the queue is empty) and counts of the current mesh.
+    pub(crate) fn stats(&self) -> TriangulationStats {
+        TriangulationStats {
+            final_max_error: self
+                .priority_queue
+                .get_max_error()
+                .copied()
+                .unwrap_or_default(),
+            iterations: self.iterations,
+            point_count: self.vertex_points.len(),
+            triangle_count: self.triangles.len() / 3,
+        }
+    }
+
+    /// Copies current state of the triangulation into a [`Mesh`].
+    pub(crate) fn mesh(&self) -> Mesh {
+        Mesh::new(
+            self.get_vertext_points(),
+            self.get_triangle_indices()
+                .into_iter()
+                .flat_map(|(a, b, c)| [a, b, c])
+                .collect(),
+            (0..self.half_edges.len())
+                .map(|index| {
+                    self.half_edge(self.output_edge(index))
+                        .map(|half_edge| self.output_edge(half_edge))
+                })
+                .collect(),
+            self.priority_queue
+                .get_max_error()
+                .copied()
+                .unwrap_or_default(),
+        )
+    }
+
+    pub(crate) fn get_triangle_indices(&self) -> Vec<(usize, usize, usize)> {
+        (0..self.triangles.len() / 3)
+            .map(|triangle_index| self.output_triangle(triangle_index))
+            .collect()
+    }
+
+    pub(crate) fn get_vertext_points(&self) -> Vec<(usize, usize)> {
+        self.vertex_points
+            .iter()
+            .map(|&point| self.output_point(point))
+            .collect()
+    }
+
+    /// Passes all points, then all triangles to the sink without collecting them.
+    pub(crate) fn emit<S: MeshSink + ?Sized>(&self, sink: &mut S) {
+        for &point in &self.vertex_points {
+            sink.on_point(self.output_point(point));
         }
+        for triangle_index in 0..self.triangles.len() / 3 {
+            sink.on_triangle(self.output_triangle(triangle_index));
+        }
+    }
 
-        Ok((self.get_vertext_points(), self.get_triangle_indices()))
+    /// Returns points with heights sampled from the grid at their exact coordinates.
+    pub(crate) fn get_vertex_points_with_heights(&self) -> Vec<PointWithHeight> {
+        self.vertex_points
+            .iter()
+            .map(|&(x, y)| {
+                let (output_x, output_y) = self.output_point((x, y));
+                (
+                    output_x,
+                    output_y,
+                    self.height_data[self.height_index((x, y))].to_f64(),
+                )
+            })
+            .collect()
     }
 
-    fn get_triangle_indices(&self) -> Vec<(usize, usize, usize)> {
-        let mut triangles = Vec::new();
+    /// Candidate point of every triangle with its error, the point refinement would insert
+    /// when splitting the triangle.
+    pub(crate) fn candidates(&self) -> Vec<(Point, Error)> {
+        (0..self.triangles.len() / 3)
+            .map(|triangle_index| {
+                (
+                    self.output_point(self.candidate_points[triangle_index]),
+                    self.priority_queue
+                        .get_error(triangle_index)
+                        .unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
 
-        for i in 0..self.triangles.len() / 3 {
-            let vertex_a_point_index = self.triangles[i * 3];
-            let vertex_b_point_index = self.triangles[i * 3 + 1];
-            let vertex_c_point_index = self.triangles[i * 3 + 2];
+    /// Triangle as returned to the caller, reversed if `flip_y` is set.
+    fn output_triangle(&self, triangle_index: usize) -> Triangle {
+        let vertex_a_point_index = self.vertex(triangle_index * 3);
+        let vertex_b_point_index = self.vertex(triangle_index * 3 + 1);
+        let vertex_c_point_index = self.vertex(triangle_index * 3 + 2);
 
-            triangles.push((
+        if self.flip_y {
+            (
+                vertex_a_point_index,
+                vertex_c_point_index,
+                vertex_b_point_index,
+            )
+        } else {
+            (
                 vertex_a_point_index,
                 vertex_b_point_index,
                 vertex_c_point_index,
-            ));
+            )
         }
-
-        triangles
     }
 
-    fn get_vertext_points(&self) -> Vec<(usize, usize)> {
-        let mut points = Vec::new();
+    /// Point as returned to the caller, flipped if `flip_y` is set.
+    fn output_point(&self, point: Point) -> Point {
+        let (x, y) = if self.flip_y {
+            (point.0, self.height - 1 - point.1)
+        } else {
+            point
+        };
 
-        for i in 0..self.vertex_points.len() {
-            let vertex_point = self.vertex_points[i];
+        (x + self.output_offset.0, y + self.output_offset.1)
+    }
 
-            points.push(vertex_point);
+    /// Maps between half-edge indices of the output and internal triangles, which are the
+    /// same but reversed if `flip_y` is set. Reversing `(a, b, c)` to `(a, c, b)` makes edge
+    /// `a -> b` the last one and `c -> a` the first one, so the mapping is its own inverse.
+    fn output_edge(&self, edge: usize) -> usize {
+        if self.flip_y {
+            let base_index = edge - edge % 3;
+            base_index + 2 - edge % 3
+        } else {
+            edge
         }
-
-        points
     }
 
     fn refine(&mut self) -> Result<(), TriangulationError> {
         self.step()?;
         self.flush();
+        self.iterations += 1;
 
         Ok(())
     }
 
     fn step(&mut self) -> Result<(), TriangulationError> {
+        #[cfg(feature = "log")]
+        log::trace!(
+            "Refinement step {} splits triangle with error {}",
+            self.iterations,
+            self.priority_queue
+                .get_max_error()
+                .copied()
+                .unwrap_or_default()
+        );
+
         // pop triangle with highest error from priority queue
         let queued_triangle = self
             .priority_queue
             .pop()
             .ok_or(TriangulationError::EmptyQueueError)?;
 
-        let vertex_a_triangle_index = queued_triangle * 3;
-        let vertex_b_triangle_index = queued_triangle * 3 + 1;
-        let vertex_c_triangle_index = queued_triangle * 3 + 2;
+        let candidate_point = self.candidate_points[queued_triangle];
+        self.split_triangle(queued_triangle, candidate_point);
+
+        Ok(())
+    }
+
+    /// Inserts point into the triangulation regardless of its error.
+    /// Points which are already vertices or lie outside of the grid are ignored.
+    fn insert_point(&mut self, point: Point) {
+        if self.find_vertex(point).is_some() {
+            return;
+        }
+
+        let Some(triangle_index) = self.find_triangle(point) else {
+            return;
+        };
+
+        self.walk_start = triangle_index;
+        self.priority_queue.remove(triangle_index);
+        self.split_triangle(triangle_index, point);
+        self.flush();
+    }
+
+    /// Inserts both ends of the segment and makes a chain of constrained edges between them.
+    ///
+    /// If the edge between ends doesn't exist, the segment is split at the pixel nearest to its
+    /// middle, diagonal steps between neighbouring pixels go through one of the other two corners.
+    /// Edges which still can't be made, e.g. crossed by another breakline, are left out.
+    fn insert_breakline(&mut self, start: Point, end: Point) {
+        self.insert_point(start);
+        self.insert_point(end);
+
+        let (Some(start_vertex), Some(end_vertex)) =
+            (self.find_vertex(start), self.find_vertex(end))
+        else {
+            return;
+        };
+        if start_vertex == end_vertex {
+            return;
+        }
+
+        if self.has_edge(start_vertex, end_vertex) {
+            self.constrained_edges
+                .insert(edge_key(start_vertex, end_vertex));
+            return;
+        }
+
+        let middle = if start.0.abs_diff(end.0) == 1 && start.1.abs_diff(end.1) == 1 {
+            (end.0, start.1)
+        } else {
+            ((start.0 + end.0) / 2, (start.1 + end.1) / 2)
+        };
+        if middle == start || middle == end {
+            return;
+        }
+
+        self.insert_breakline(start, middle);
+        self.insert_breakline(middle, end);
+    }
+
+    /// Checks whether any triangle has both vertices, i.e. they are connected by an edge.
+    fn has_edge(&self, vertex_a: usize, vertex_b: usize) -> bool {
+        (0..self.triangles.len() / 3).any(|triangle_index| {
+            let vertices = [0, 1, 2].map(|offset| self.vertex(triangle_index * 3 + offset));
+            vertices.contains(&vertex_a) && vertices.contains(&vertex_b)
+        })
+    }
+
+    /// Checks whether the edge between vertices is part of a breakline.
+    fn is_constrained_edge(&self, vertex_a: usize, vertex_b: usize) -> bool {
+        !self.constrained_edges.is_empty()
+            && self
+                .constrained_edges
+                .contains(&edge_key(vertex_a, vertex_b))
+    }
+
+    /// Finds vertex at the point, indexing all vertices on the first call.
+    fn find_vertex(&mut self, point: Point) -> Option<usize> {
+        let vertex_points = &self.vertex_points;
+        let vertex_lookup = self.vertex_lookup.get_or_insert_with(|| {
+            let mut vertex_lookup = HashMap::with_capacity(vertex_points.len());
+            for (vertex, &point) in vertex_points.iter().enumerate() {
+                vertex_lookup.entry(point).or_insert(vertex);
+            }
+            vertex_lookup
+        });
+
+        vertex_lookup.get(&point).copied()
+    }
+
+    /// Finds triangle which contains point, including its edges, by walking from the triangle
+    /// of the last insertion across edges which have the point on their outer side.
+    fn find_triangle(&self, point: Point) -> Option<usize> {
+        let triangle_count = self.triangles.len() / 3;
+        let mut triangle_index = self.walk_start.min(triangle_count.checked_sub(1)?);
+
+        // walks may circle around constrained edges of non-Delaunay meshes, so they are
+        // bounded and fall back to checking all triangles
+        for _ in 0..triangle_count {
+            let points = [0, 1, 2]
+                .map(|offset| self.vertex_points[self.vertex(triangle_index * 3 + offset)]);
+            let outer_edge = (0..3).find(|&edge| {
+                orientation(points[edge], points[(edge + 1) % 3], point) == Ordering::Less
+            });
+            match outer_edge {
+                // edge on the boundary of the grid, so the point is outside of it
+                Some(edge) => triangle_index = self.half_edge(triangle_index * 3 + edge)? / 3,
+                None => return Some(triangle_index),
+            }
+        }
 
-        let vertex_a_point_index = self.triangles[vertex_a_triangle_index];
-        let vertex_b_point_index = self.triangles[vertex_b_triangle_index];
-        let vertex_c_point_index = self.triangles[vertex_c_triangle_index];
+        self.scan_for_triangle(point)
+    }
+
+    /// Finds triangle which contains point, including its edges, checking all triangles.
+    fn scan_for_triangle(&self, point: Point) -> Option<usize> {
+        (0..self.triangles.len() / 3).find(|&triangle_index| {
+            let point_a = self.vertex_points[self.vertex(triangle_index * 3)];
+            let point_b = self.vertex_points[self.vertex(triangle_index * 3 + 1)];
+            let point_c = self.vertex_points[self.vertex(triangle_index * 3 + 2)];
+
+            orientation(point_b, point_c, point) != Ordering::Less
+                && orientation(point_c, point_a, point) != Ordering::Less
+                && orientation(point_a, point_b, point) != Ordering::Less
+        })
+    }
+
+    /// Splits triangle, which is already removed from priority queue, by new vertex at the point.
+    fn split_triangle(&mut self, triangle_index: usize, candidate_point: Point) {
+        let vertex_a_triangle_index = triangle_index * 3;
+        let vertex_b_triangle_index = triangle_index * 3 + 1;
+        let vertex_c_triangle_index = triangle_index * 3 + 2;
+
+        let vertex_a_point_index = self.vertex(vertex_a_triangle_index);
+        let vertex_b_point_index = self.vertex(vertex_b_triangle_index);
+        let vertex_c_point_index = self.vertex(vertex_c_triangle_index);
 
         let point_a = self.vertex_points[vertex_a_point_index];
         let point_b = self.vertex_points[vertex_b_point_index];
         let point_c = self.vertex_points[vertex_c_point_index];
 
-        let candidate_point = self.candidate_points[queued_triangle];
         let new_vertex_index = self.add_point(candidate_point);
 
         // check if candidate point is collinear with any of the triangle points
-        if get_signed_area(point_a, point_b, candidate_point) == 0 {
+        if orientation(point_a, point_b, candidate_point) == Ordering::Equal {
             self.handle_collinear(new_vertex_index, vertex_a_triangle_index);
-        } else if get_signed_area(point_b, point_c, candidate_point) == 0 {
+        } else if orientation(point_b, point_c, candidate_point) == Ordering::Equal {
             self.handle_collinear(new_vertex_index, vertex_b_triangle_index);
-        } else if get_signed_area(point_c, point_a, candidate_point) == 0 {
+        } else if orientation(point_c, point_a, candidate_point) == Ordering::Equal {
             self.handle_collinear(new_vertex_index, vertex_c_triangle_index);
         } else {
-            let half_edge_a = self.half_edges[vertex_a_triangle_index];
-            let half_edge_b = self.half_edges[vertex_b_triangle_index];
-            let half_edge_c = self.half_edges[vertex_c_triangle_index];
+            let half_edge_a = self.half_edge(vertex_a_triangle_index);
+            let half_edge_b = self.half_edge(vertex_b_triangle_index);
+            let half_edge_c = self.half_edge(vertex_c_triangle_index);
 
             let new_triangle_0 = self.add_triangle(
                 (vertex_a_point_index, vertex_b_point_index, new_vertex_index),
@@ -218,8 +979,6 @@ impl<'a> Triangulation<'a> {
             self.legalize(new_triangle_1);
             self.legalize(new_triangle_2);
         }
-
-        Ok(())
     }
 
     fn handle_collinear(&mut self, new_vertex_index: usize, collinear_vertex_index: usize) {
@@ -227,23 +986,35 @@ impl<'a> Triangulation<'a> {
         let vertex_a_triangle_index = collinear_base_index + (collinear_vertex_index + 1) % 3;
         let vertex_b_triangle_index = collinear_base_index + (collinear_vertex_index + 2) % 3;
 
-        let collinear_vertex_point_index = self.triangles[collinear_vertex_index];
-        let vertex_a_point_index = self.triangles[vertex_a_triangle_index];
-        let vertex_b_point_index = self.triangles[vertex_b_triangle_index];
+        let collinear_vertex_point_index = self.vertex(collinear_vertex_index);
+        let vertex_a_point_index = self.vertex(vertex_a_triangle_index);
+        let vertex_b_point_index = self.vertex(vertex_b_triangle_index);
+
+        let half_edge_a = self.half_edge(vertex_a_triangle_index);
+        let half_edge_b = self.half_edge(vertex_b_triangle_index);
 
-        let half_edge_a = self.half_edges[vertex_a_triangle_index];
-        let half_edge_b = self.half_edges[vertex_b_triangle_index];
+        // both halves of a split breakline edge stay constrained
+        if self.is_constrained_edge(collinear_vertex_point_index, vertex_a_point_index) {
+            self.constrained_edges.remove(&edge_key(
+                collinear_vertex_point_index,
+                vertex_a_point_index,
+            ));
+            self.constrained_edges
+                .insert(edge_key(collinear_vertex_point_index, new_vertex_index));
+            self.constrained_edges
+                .insert(edge_key(new_vertex_index, vertex_a_point_index));
+        }
 
-        match self.half_edges[collinear_vertex_index] {
+        match self.half_edge(collinear_vertex_index) {
             Some(collinear_half_edge) => {
                 let adjacent_triangle_base_index = collinear_half_edge - collinear_half_edge % 3;
                 let adjacent_left_edge_index =
                     adjacent_triangle_base_index + (collinear_half_edge + 2) % 3;
                 let adjacent_right_edge_index =
                     adjacent_triangle_base_index + (collinear_half_edge + 1) % 3;
-                let vertex1 = self.triangles[adjacent_left_edge_index];
-                let half_edge_adjacent_left = self.half_edges[adjacent_left_edge_index];
-                let half_edge_adjacent_right = self.half_edges[adjacent_right_edge_index];
+                let vertex1 = self.vertex(adjacent_left_edge_index);
+                let half_edge_adjacent_left = self.half_edge(adjacent_left_edge_index);
+                let half_edge_adjacent_right = self.half_edge(adjacent_right_edge_index);
 
                 self.priority_queue.remove(adjacent_triangle_base_index / 3);
 
@@ -312,13 +1083,31 @@ impl<'a> Triangulation<'a> {
 
     /// If the pair of triangles doesn't satisfy the Delaunay condition
     /// (vertex_1 is inside the circumcircle of \[vertex_0, vertex_right, vertex_left\]), flip them,
-    /// then do the same check/flip recursively for the new pair of triangles
+    /// then do the same check/flip for the new pair of triangles.
+    /// Edges of breaklines are never flipped.
+    ///
+    /// Edges left to check are kept on an explicit stack instead of recursing, so long chains
+    /// of flips don't overflow the call stack. They are checked in the same order as recursion.
     fn legalize(&mut self, requested_triangle_index: usize) {
+        let mut edges_to_check = vec![requested_triangle_index];
+        while let Some(edge) = edges_to_check.pop() {
+            if let Some((new_edge_0, new_edge_1)) = self.flip_if_illegal(edge) {
+                // pushed in reverse, so the first new edge is checked first
+                edges_to_check.push(new_edge_1);
+                edges_to_check.push(new_edge_0);
+            }
+        }
+    }
+
+    /// Flips the pair of triangles of the edge if it isn't legal.
+    ///
+    /// # Returns
+    ///
+    /// Edges of the new pair of triangles to check next, `None` if nothing was flipped.
+    fn flip_if_illegal(&mut self, requested_triangle_index: usize) -> Option<(usize, usize)> {
         // find half edge of requested triangle
         // half edge will form adjacent triangle with same edge as requested
-        let Some(half_edge) = self.half_edges[requested_triangle_index] else {
-            return;
-        };
+        let half_edge = self.half_edge(requested_triangle_index)?;
 
         let requested_triangle_base_index = requested_triangle_index - requested_triangle_index % 3;
         let adjacent_triangle_base_index = half_edge - half_edge % 3;
@@ -331,24 +1120,55 @@ impl<'a> Triangulation<'a> {
         let adjacent_left_edge_index = adjacent_triangle_base_index + (half_edge + 2) % 3;
         let adjacent_right_edge_index = adjacent_triangle_base_index + (half_edge + 1) % 3;
 
-        let vertex_0 = self.triangles[requested_right_edge_index];
-        let vertex_right = self.triangles[requested_triangle_index];
-        let vertex_left = self.triangles[requested_left_edge_index];
-        let vertex_1 = self.triangles[adjacent_left_edge_index];
-
-        if !is_point_in_circumcircle(
-            self.vertex_points[vertex_1],
-            self.vertex_points[vertex_0],
-            self.vertex_points[vertex_right],
-            self.vertex_points[vertex_left],
-        ) {
-            return;
+        let vertex_0 = self.vertex(requested_right_edge_index);
+        let vertex_right = self.vertex(requested_triangle_index);
+        let vertex_left = self.vertex(requested_left_edge_index);
+        let vertex_1 = self.vertex(adjacent_left_edge_index);
+        if self.is_constrained_edge(vertex_right, vertex_left) {
+            return None;
+        }
+
+        let [point_1, point_0, point_right, point_left] =
+            [vertex_1, vertex_0, vertex_right, vertex_left]
+                .map(|vertex| self.vertex_points[vertex]);
+        let is_illegal = match (self.circumcircle_tolerance, self.cell_size) {
+            (Some(tolerance), Some(cell_size)) => {
+                scaled_circumcircle_determinant(
+                    point_1,
+                    point_0,
+                    point_right,
+                    point_left,
+                    cell_size,
+                ) < -tolerance
+            }
+            (Some(tolerance), None) => {
+                circumcircle_determinant(point_1, point_0, point_right, point_left) < -tolerance
+            }
+            (None, Some(cell_size)) => is_point_in_scaled_circumcircle(
+                point_1,
+                point_0,
+                point_right,
+                point_left,
+                cell_size,
+            ),
+            (None, None) => is_point_in_circumcircle(point_1, point_0, point_right, point_left),
+        };
+        if !is_illegal {
+            return None;
         }
+        // a point inside the circumcircle beyond the shared edge always makes a strictly convex
+        // quad, also with scaled cells which only stretch the circle, so new triangles can't be
+        // degenerate or reversed unless the test itself is wrong
+        debug_assert!(
+            get_signed_area(point_0, point_1, point_left) > 0
+                && get_signed_area(point_1, point_0, point_right) > 0,
+            "Flipped edge of a non-convex quad."
+        );
 
-        let half_edge_left = self.half_edges[requested_left_edge_index];
-        let half_edge_right = self.half_edges[requested_right_edge_index];
-        let adjacent_half_edge_left = self.half_edges[adjacent_left_edge_index];
-        let adjacent_half_edge_right = self.half_edges[adjacent_right_edge_index];
+        let half_edge_left = self.half_edge(requested_left_edge_index);
+        let half_edge_right = self.half_edge(requested_right_edge_index);
+        let adjacent_half_edge_left = self.half_edge(adjacent_left_edge_index);
+        let adjacent_half_edge_right = self.half_edge(adjacent_right_edge_index);
 
         self.priority_queue
             .remove(requested_triangle_base_index / 3);
@@ -369,23 +1189,94 @@ impl<'a> Triangulation<'a> {
             AddTriangleStrategy::Update(adjacent_triangle_base_index),
         );
 
-        self.legalize(new_triangle_0 + 1);
-        self.legalize(new_triangle_1 + 2);
+        Some((new_triangle_0 + 1, new_triangle_1 + 2))
     }
 
     fn flush(&mut self) {
         let pending_triangles = self.priority_queue.consume_pending_triangles();
-        for pending_triangle in pending_triangles {
-            // rasterize triangle to find maximum pixel error
-            self.find_candidate(pending_triangle);
+        // rasterize triangles to find maximum pixel errors
+        let candidates = self.find_candidates(&pending_triangles);
+        // queue in the pending order, so ties are broken the same way however candidates were found
+        for (triangle_index, candidate) in pending_triangles.into_iter().zip(candidates) {
+            self.candidate_points[triangle_index] = candidate.point;
+            if self.rmse_target.is_some() {
+                self.set_squared_error(triangle_index, candidate.squared_error);
+            }
+            self.priority_queue
+                .push(triangle_index, candidate.max_error);
         }
     }
 
-    // rasterize a triangle, find its max error, and queue it for processing
-    fn find_candidate(&mut self, triangle_index: usize) {
-        let vertex_a_point_index = self.triangles[triangle_index * 3];
-        let vertex_b_point_index = self.triangles[triangle_index * 3 + 1];
-        let vertex_c_point_index = self.triangles[triangle_index * 3 + 2];
+    /// Replaces squared error of the triangle in the running sum.
+    fn set_squared_error(&mut self, triangle_index: usize, squared_error: f64) {
+        if triangle_index >= self.squared_errors.len() {
+            self.squared_errors.resize(triangle_index + 1, 0.0);
+        }
+
+        let previous_squared_error =
+            std::mem::replace(&mut self.squared_errors[triangle_index], squared_error);
+        if previous_squared_error.is_finite() {
+            self.squared_error_sum -= previous_squared_error;
+        } else {
+            self.infinite_error_count -= 1;
+        }
+        if squared_error.is_finite() {
+            self.squared_error_sum += squared_error;
+        } else {
+            self.infinite_error_count += 1;
+        }
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    fn find_candidates(&self, triangle_indices: &[usize]) -> Vec<Candidate> {
+        triangle_indices
+            .iter()
+            .map(|&triangle_index| self.find_candidate(triangle_index))
+            .collect()
+    }
+
+    /// Rasterizes triangles in parallel once they cover enough pixels to outweigh the overhead,
+    /// refinement steps late in the process usually have only a few small triangles.
+    #[cfg(feature = "rayon")]
+    fn find_candidates(&self, triangle_indices: &[usize]) -> Vec<Candidate> {
+        use rayon::prelude::*;
+
+        const MIN_PARALLEL_PIXELS: usize = 1 << 16;
+
+        let pixel_count = triangle_indices
+            .iter()
+            .map(|&triangle_index| {
+                let [point_a, point_b, point_c] =
+                    [0, 1, 2].map(|i| self.vertex_points[self.vertex(triangle_index * 3 + i)]);
+                let width = point_a.0.max(point_b.0).max(point_c.0)
+                    - point_a.0.min(point_b.0).min(point_c.0)
+                    + 1;
+                let height = point_a.1.max(point_b.1).max(point_c.1)
+                    - point_a.1.min(point_b.1).min(point_c.1)
+                    + 1;
+
+                width * height
+            })
+            .sum::<usize>();
+
+        if pixel_count < MIN_PARALLEL_PIXELS {
+            return triangle_indices
+                .iter()
+                .map(|&triangle_index| self.find_candidate(triangle_index))
+                .collect();
+        }
+
+        triangle_indices
+            .par_iter()
+            .map(|&triangle_index| self.find_candidate(triangle_index))
+            .collect()
+    }
+
+    // rasterize a triangle, find its max error and the point where it is
+    fn find_candidate(&self, triangle_index: usize) -> Candidate {
+        let vertex_a_point_index = self.vertex(triangle_index * 3);
+        let vertex_b_point_index = self.vertex(triangle_index * 3 + 1);
+        let vertex_c_point_index = self.vertex(triangle_index * 3 + 2);
         let point_a = self.vertex_points[vertex_a_point_index];
         let point_b = self.vertex_points[vertex_b_point_index];
         let point_c = self.vertex_points[vertex_c_point_index];
@@ -408,22 +1299,62 @@ impl<'a> Triangulation<'a> {
             .max()
             .expect("No items to get max from.");
 
-        let triangle_abc_signed_area = get_signed_area(point_a, point_b, point_c);
+        // void vertices can't predict heights, so valid pixels of their triangles are
+        // interpolated from valid vertices only
+        let vertex_heights = [point_a, point_b, point_c]
+            .map(|point| (!self.is_nodata(point)).then(|| self.height_at(point)));
+        let has_nodata_vertex = vertex_heights.contains(&None);
+
         let mut triangle_bcmin_signed_area = get_signed_area(point_b, point_c, (min_x, min_y));
         let mut triangle_camin_signed_area = get_signed_area(point_c, point_a, (min_x, min_y));
         let mut triangle_abmin_signed_area = get_signed_area(point_a, point_b, (min_x, min_y));
 
-        let ba_y_diff = point_b.1 as isize - point_a.1 as isize;
-        let ab_x_diff = point_a.0 as isize - point_b.0 as isize;
-        let cb_y_diff = point_c.1 as isize - point_b.1 as isize;
-        let bc_x_diff = point_b.0 as isize - point_c.0 as isize;
-        let ac_y_diff = point_a.1 as isize - point_c.1 as isize;
-        let ca_x_diff = point_c.0 as isize - point_a.0 as isize;
+        let ba_y_diff = point_b.1 as i64 - point_a.1 as i64;
+        let ab_x_diff = point_a.0 as i64 - point_b.0 as i64;
+        let cb_y_diff = point_c.1 as i64 - point_b.1 as i64;
+        let bc_x_diff = point_b.0 as i64 - point_c.0 as i64;
+        let ac_y_diff = point_a.1 as i64 - point_c.1 as i64;
+        let ca_x_diff = point_c.0 as i64 - point_a.0 as i64;
 
+        let triangle_abc_signed_area = get_signed_area(point_a, point_b, point_c);
         let normalized_height_at_a = self.height_at(point_a) / triangle_abc_signed_area as f64;
         let normalized_height_at_b = self.height_at(point_b) / triangle_abc_signed_area as f64;
         let normalized_height_at_c = self.height_at(point_c) / triangle_abc_signed_area as f64;
 
+        // pixels on an edge shared by two triangles are summed into squared error by only one
+        // of them, decided by the edge direction which is opposite in the other triangle
+        let owns_edge = |half_edge: usize, start: Point, end: Point| {
+            let delta_x = end.0 as i64 - start.0 as i64;
+            let delta_y = end.1 as i64 - start.1 as i64;
+
+            self.half_edge(triangle_index * 3 + half_edge).is_none()
+                || delta_y > 0
+                || (delta_y == 0 && delta_x < 0)
+        };
+        let owns_edge_ab = owns_edge(0, point_a, point_b);
+        let owns_edge_bc = owns_edge(1, point_b, point_c);
+        let owns_edge_ca = owns_edge(2, point_c, point_a);
+        let mut squared_error = 0.0;
+
+        // midpoint of the longest edge if it exceeds max edge length
+        let long_edge_midpoint = self.max_edge_length.and_then(|max_edge_length| {
+            let (cell_width, cell_height) = self.cell_size.unwrap_or((1.0, 1.0));
+            [(point_a, point_b), (point_b, point_c), (point_c, point_a)]
+                .into_iter()
+                .map(|(start, end)| {
+                    let delta_x = (end.0 as f64 - start.0 as f64) * cell_width;
+                    let delta_y = (end.1 as f64 - start.1 as f64) * cell_height;
+                    let midpoint = (
+                        (start.0 + end.0) as f64 / 2.0,
+                        (start.1 + end.1) as f64 / 2.0,
+                    );
+
+                    (delta_x.hypot(delta_y), midpoint)
+                })
+                .filter(|(length, _)| *length > max_edge_length)
+                .max_by(|(length_a, _), (length_b, _)| length_a.total_cmp(length_b))
+                .map(|(_, midpoint)| midpoint)
+        });
         // iterate over pixels in bounding box
         let mut max_error = Error(0.0);
         let mut max_error_point: Point = (0, 0);
@@ -443,7 +1374,15 @@ impl<'a> Triangulation<'a> {
                     .max(((-triangle_abmin_signed_area / ba_y_diff) as f32).floor() as usize);
             }
 
-            let signed_offset_x = offset_x as isize;
+            // skip pixels outside of the region
+            offset_x = offset_x.max(self.region.0.saturating_sub(min_x));
+            let row_end_x = if (self.region.1..=self.region.3).contains(&y) {
+                max_x.min(self.region.2) + 1
+            } else {
+                0
+            };
+
+            let signed_offset_x = offset_x as i64;
             let mut triangle_bcmin_adjusted_signed_area =
                 triangle_bcmin_signed_area + cb_y_diff * signed_offset_x;
             let mut triangle_camin_adjusted_signed_area =
@@ -453,7 +1392,7 @@ impl<'a> Triangulation<'a> {
 
             let mut was_inside = false;
 
-            for x in min_x + offset_x..=max_x {
+            for x in min_x + offset_x..row_end_x {
                 // check if inside triangle
                 if triangle_bcmin_adjusted_signed_area >= 0
                     && triangle_camin_adjusted_signed_area >= 0
@@ -462,14 +1401,55 @@ impl<'a> Triangulation<'a> {
                     was_inside = true;
 
                     // compute z using barycentric coordinates
-                    let z = normalized_height_at_a * (triangle_bcmin_adjusted_signed_area as f64)
+                    let mut z = normalized_height_at_a
+                        * (triangle_bcmin_adjusted_signed_area as f64)
                         + normalized_height_at_b * (triangle_camin_adjusted_signed_area as f64)
                         + normalized_height_at_c * (triangle_abmin_adjusted_signed_area as f64);
-                    let z_diff = (z - self.height_at((x, y))).abs();
+                    let mut is_uncovered = false;
+                    if has_nodata_vertex {
+                        let weights = [
+                            triangle_bcmin_adjusted_signed_area,
+                            triangle_camin_adjusted_signed_area,
+                            triangle_abmin_adjusted_signed_area,
+                        ];
+                        z = interpolate_valid_vertices(vertex_heights, weights.map(|w| w as f64))
+                            .unwrap_or(f64::NAN);
+                        // pixels off edges between valid vertices are lost with the triangle
+                        is_uncovered = z.is_nan()
+                            || self.cover_valid_pixels
+                                && (0..3).any(|i| vertex_heights[i].is_none() && weights[i] > 0);
+                    }
+                    let mut z_diff = self.metric.error(z, self.height_at((x, y)), (x, y));
+                    if let Some(confidence) = self.confidence {
+                        z_diff *= confidence[self.height_index((x, y))];
+                    }
+                    if self.nodata.is_some() {
+                        if self.is_nodata((x, y)) {
+                            z_diff = 0.0;
+                        } else if is_uncovered {
+                            z_diff = f64::INFINITY;
+                        }
+                    }
+
+                    if self.rmse_target.is_some()
+                        && (triangle_bcmin_adjusted_signed_area > 0 || owns_edge_bc)
+                        && (triangle_camin_adjusted_signed_area > 0 || owns_edge_ca)
+                        && (triangle_abmin_adjusted_signed_area > 0 || owns_edge_ab)
+                    {
+                        squared_error += z_diff * z_diff;
+                    }
 
                     if z_diff > max_error.0 {
                         max_error = Error(z_diff);
                         max_error_point = (x, y);
+                    } else if z_diff == max_error.0 {
+                        // break ties pseudo-randomly instead of in scan order
+                        if let Some(seed) = self.jitter_seed {
+                            if tie_break_hash((x, y), seed) > tie_break_hash(max_error_point, seed)
+                            {
+                                max_error_point = (x, y);
+                            }
+                        }
                     }
                 } else if was_inside {
                     break;
@@ -489,17 +1469,90 @@ impl<'a> Triangulation<'a> {
             max_error = Error(0.0);
         }
 
-        self.candidate_points[triangle_index] = max_error_point;
-        self.priority_queue.push(triangle_index, max_error);
+        // too long edge forces refinement until triangle can't be split anymore
+        if let Some(point) = long_edge_midpoint
+            .and_then(|midpoint| self.find_closest_inner_point(point_a, point_b, point_c, midpoint))
+        {
+            max_error = Error(f64::INFINITY);
+            max_error_point = point;
+        }
+
+        Candidate {
+            point: max_error_point,
+            max_error,
+            squared_error,
+        }
+    }
+
+    /// Finds pixel of the triangle, other than its vertices, closest to the target.
+    fn find_closest_inner_point(
+        &self,
+        point_a: Point,
+        point_b: Point,
+        point_c: Point,
+        target: (f64, f64),
+    ) -> Option<Point> {
+        let min_x = point_a.0.min(point_b.0).min(point_c.0);
+        let min_y = point_a.1.min(point_b.1).min(point_c.1);
+        let max_x = point_a.0.max(point_b.0).max(point_c.0);
+        let max_y = point_a.1.max(point_b.1).max(point_c.1);
+
+        (min_y..=max_y)
+            .flat_map(|y| (min_x..=max_x).map(move |x| (x, y)))
+            .filter(|&point| {
+                point != point_a
+                    && point != point_b
+                    && point != point_c
+                    && orientation(point_b, point_c, point) != Ordering::Less
+                    && orientation(point_c, point_a, point) != Ordering::Less
+                    && orientation(point_a, point_b, point) != Ordering::Less
+            })
+            .min_by(|point_0, point_1| {
+                let distance_0 = (point_0.0 as f64 - target.0).hypot(point_0.1 as f64 - target.1);
+                let distance_1 = (point_1.0 as f64 - target.0).hypot(point_1.1 as f64 - target.1);
+                distance_0.total_cmp(&distance_1)
+            })
+    }
+
+    #[cfg_attr(not(feature = "u32-indices"), allow(clippy::unnecessary_cast))]
+    fn vertex(&self, index: usize) -> usize {
+        self.triangles[index] as usize
+    }
+
+    #[cfg_attr(not(feature = "u32-indices"), allow(clippy::unnecessary_cast))]
+    fn half_edge(&self, index: usize) -> Option<usize> {
+        let half_edge = self.half_edges[index];
+        (half_edge != NO_HALF_EDGE).then_some(half_edge as usize)
+    }
+
+    fn set_half_edge(&mut self, index: usize, half_edge: Option<usize>) {
+        self.half_edges[index] = to_stored_half_edge(half_edge);
+    }
+
+    fn height_index(&self, point: Point) -> usize {
+        self.stride * point.1 + point.0
     }
 
     fn height_at(&self, point: Point) -> Height {
-        self.height_data[self.width * point.1 + point.0]
+        let height = self.height_data[self.height_index(point)].to_f64();
+        match self.clip_below {
+            Some(clip_below) => height.max(clip_below),
+            None => height,
+        }
+    }
+
+    fn is_nodata(&self, point: Point) -> bool {
+        self.nodata.is_some_and(|nodata| {
+            is_nodata_value(self.height_data[self.height_index(point)].to_f64(), nodata)
+        })
     }
 
     fn add_point(&mut self, point: Point) -> usize {
         let index = self.vertex_points.len();
         self.vertex_points.push(point);
+        if let Some(vertex_lookup) = &mut self.vertex_lookup {
+            vertex_lookup.entry(point).or_insert(index);
+        }
 
         index
     }
@@ -514,25 +1567,25 @@ impl<'a> Triangulation<'a> {
     ) -> usize {
         let index_to_add = match add_strategy {
             AddTriangleStrategy::Update(index) => {
-                self.triangles[index] = triangle.0;
-                self.triangles[index + 1] = triangle.1;
-                self.triangles[index + 2] = triangle.2;
+                self.triangles[index] = to_index(triangle.0);
+                self.triangles[index + 1] = to_index(triangle.1);
+                self.triangles[index + 2] = to_index(triangle.2);
 
-                self.half_edges[index] = half_edge_ab;
-                self.half_edges[index + 1] = half_edge_bc;
-                self.half_edges[index + 2] = half_edge_ca;
+                self.set_half_edge(index, half_edge_ab);
+                self.set_half_edge(index + 1, half_edge_bc);
+                self.set_half_edge(index + 2, half_edge_ca);
 
                 index
             }
             AddTriangleStrategy::Create => {
                 let index = self.triangles.len();
-                self.triangles.push(triangle.0);
-                self.triangles.push(triangle.1);
-                self.triangles.push(triangle.2);
+                self.triangles.push(to_index(triangle.0));
+                self.triangles.push(to_index(triangle.1));
+                self.triangles.push(to_index(triangle.2));
 
-                self.half_edges.push(half_edge_ab);
-                self.half_edges.push(half_edge_bc);
-                self.half_edges.push(half_edge_ca);
+                self.half_edges.push(to_stored_half_edge(half_edge_ab));
+                self.half_edges.push(to_stored_half_edge(half_edge_bc));
+                self.half_edges.push(to_stored_half_edge(half_edge_ca));
 
                 index
             }
@@ -542,13 +1595,13 @@ impl<'a> Triangulation<'a> {
 
         // link adjacent half edges
         if let Some(half_edge_ab) = half_edge_ab {
-            self.half_edges[half_edge_ab] = Some(index_to_add);
+            self.set_half_edge(half_edge_ab, Some(index_to_add));
         }
         if let Some(half_edge_bc) = half_edge_bc {
-            self.half_edges[half_edge_bc] = Some(index_to_add + 1);
+            self.set_half_edge(half_edge_bc, Some(index_to_add + 1));
         }
         if let Some(half_edge_ca) = half_edge_ca {
-            self.half_edges[half_edge_ca] = Some(index_to_add + 2);
+            self.set_half_edge(half_edge_ca, Some(index_to_add + 2));
         }
 
         self.candidate_points.push((0, 0));
@@ -557,3 +1610,209 @@ impl<'a> Triangulation<'a> {
         index_to_add
     }
 }
+
+/// Height interpolated with barycentric weights of valid vertices only, `None` without any.
+/// Pixels where all valid vertices have zero weight lie on an edge between void vertices and
+/// get the mean of valid heights.
+fn interpolate_valid_vertices(heights: [Option<Height>; 3], weights: [f64; 3]) -> Option<Height> {
+    let mut weighted_sum = 0.0;
+    let mut weight_sum = 0.0;
+    let mut height_sum = 0.0;
+    let mut valid_count = 0;
+    for (height, weight) in heights.into_iter().zip(weights) {
+        let Some(height) = height else {
+            continue;
+        };
+        weighted_sum += height * weight;
+        weight_sum += weight;
+        height_sum += height;
+        valid_count += 1;
+    }
+
+    match (valid_count, weight_sum > 0.0) {
+        (0, _) => None,
+        (_, true) => Some(weighted_sum / weight_sum),
+        (_, false) => Some(height_sum / valid_count as f64),
+    }
+}
+
+fn to_stored_half_edge(half_edge: Option<usize>) -> Index {
+    half_edge.map_or(NO_HALF_EDGE, to_index)
+}
+
+/// Converts vertex or half-edge index to the stored type. Grids are checked against
+/// `MAX_PIXELS` and meshes passed in from outside against the size of their grid up front,
+/// so overflowing it is a bug.
+fn to_index(index: usize) -> Index {
+    match Index::try_from(index) {
+        Ok(index) if index != NO_HALF_EDGE => index,
+        _ => panic!("Index {index} doesn't fit the stored index type."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::{size_of, size_of_val};
+
+    fn refined(heights: &[f64], size: usize) -> Triangulation<'_> {
+        let mut triangulation = Triangulation::new(heights, size, size);
+        triangulation.run(Error(0.5)).unwrap();
+
+        triangulation
+    }
+
+    fn waves(size: usize) -> Vec<f64> {
+        (0..size * size)
+            .map(|i| ((i % size) as f64 / 3.0).sin() * ((i / size) as f64 / 5.0).cos() * 50.0)
+            .collect()
+    }
+
+    /// Rows of the square grid followed by `padding` values, like a region of a wider grid.
+    fn padded(values: &[f64], size: usize, padding: f64) -> Vec<f64> {
+        values
+            .chunks_exact(size)
+            .flat_map(|row| row.iter().copied().chain([padding; 3]))
+            .collect()
+    }
+
+    #[test]
+    fn strided_confidence_matches_copied_region() {
+        let heights = waves(17);
+        let confidence = (0..17 * 17)
+            .map(|i| ((i * 5) % 7) as f64 / 6.0)
+            .collect::<Vec<f64>>();
+        let mut copied = Triangulation::new(&heights, 17, 17);
+        copied.set_confidence(&confidence);
+        copied.run(Error(0.5)).unwrap();
+
+        let padded_heights = padded(&heights, 17, 1000.0);
+        let padded_confidence = padded(&confidence, 17, 0.0);
+        let mut strided = Triangulation::new(&padded_heights, 17, 17);
+        strided.set_stride(20);
+        strided.set_confidence(&padded_confidence);
+        strided.run(Error(0.5)).unwrap();
+
+        assert_eq!(strided.mesh(), copied.mesh());
+    }
+
+    #[test]
+    fn strided_extrema_match_copied_region() {
+        let heights = waves(17);
+        let mut copied = Triangulation::new(&heights, 17, 17);
+        copied.set_preserve_extrema(true);
+        copied.run(Error(5.0)).unwrap();
+
+        let padded_heights = padded(&heights, 17, 0.0);
+        let mut strided = Triangulation::new(&padded_heights, 17, 17);
+        strided.set_stride(20);
+        strided.set_preserve_extrema(true);
+        strided.run(Error(5.0)).unwrap();
+
+        assert_eq!(strided.mesh(), copied.mesh());
+    }
+
+    #[test]
+    fn u32_indices_halve_stored_indices() {
+        let heights = waves(65);
+        let triangulation = refined(&heights, 65);
+        let index_count = triangulation.triangles.len() + triangulation.half_edges.len();
+        let stored_bytes = index_count * size_of::<Index>();
+
+        if cfg!(feature = "u32-indices") {
+            assert_eq!(size_of::<Index>(), 4);
+            assert_eq!(2 * stored_bytes, index_count * size_of::<u64>());
+        } else {
+            assert_eq!(stored_bytes, index_count * size_of::<usize>());
+        }
+    }
+
+    #[test]
+    fn sentinel_half_edges_match_option_topology() {
+        let heights = waves(129);
+        let triangulation = refined(&heights, 129);
+        let half_edges = (0..triangulation.half_edges.len())
+            .map(|index| triangulation.half_edge(index))
+            .collect::<Vec<Option<usize>>>();
+        let triangles = (0..triangulation.triangles.len() / 3)
+            .map(|triangle_index| {
+                let [a, b, c] =
+                    [0, 1, 2].map(|offset| triangulation.vertex(triangle_index * 3 + offset));
+                (a, b, c)
+            })
+            .collect::<Vec<Triangle>>();
+        let rebuilt = Mesh::from_triangles(triangulation.vertex_points.clone(), &triangles);
+
+        assert!(half_edges.iter().any(Option::is_none));
+        assert_eq!(half_edges, rebuilt.half_edges());
+        for (index, &half_edge) in half_edges.iter().enumerate() {
+            assert_eq!(
+                to_stored_half_edge(half_edge),
+                triangulation.half_edges[index]
+            );
+        }
+
+        let stored_bytes = size_of_val(triangulation.half_edges.as_slice());
+        let option_bytes = size_of_val(half_edges.as_slice());
+        assert_eq!(size_of::<Option<usize>>(), 2 * size_of::<usize>());
+        assert!(2 * stored_bytes <= option_bytes);
+    }
+
+    #[test]
+    fn walk_finds_triangle_containing_every_pixel() {
+        let heights = waves(33);
+        let mut triangulation = refined(&heights, 33);
+        let contains = |triangulation: &Triangulation, triangle_index: usize, point: Point| {
+            (0..3).all(|edge| {
+                let start =
+                    triangulation.vertex_points[triangulation.vertex(triangle_index * 3 + edge)];
+                let end = triangulation.vertex_points
+                    [triangulation.vertex(triangle_index * 3 + (edge + 1) % 3)];
+                orientation(start, end, point) != Ordering::Less
+            })
+        };
+
+        let triangle_count = triangulation.triangles.len() / 3;
+        for walk_start in [0, triangle_count / 2, triangle_count - 1] {
+            triangulation.walk_start = walk_start;
+            for point in (0..33).flat_map(|y| (0..33).map(move |x| (x, y))) {
+                let triangle_index = triangulation.find_triangle(point).unwrap();
+                assert!(contains(&triangulation, triangle_index, point));
+            }
+        }
+        assert_eq!(triangulation.find_triangle((33, 0)), None);
+    }
+
+    #[test]
+    fn vertex_lookup_follows_added_points() {
+        let heights = waves(17);
+        let mut triangulation = refined(&heights, 17);
+
+        for (vertex, &point) in triangulation.vertex_points.clone().iter().enumerate() {
+            assert_eq!(triangulation.find_vertex(point), Some(vertex));
+        }
+        let point = (0..17)
+            .flat_map(|y| (0..17).map(move |x| (x, y)))
+            .find(|point| !triangulation.vertex_points.contains(point))
+            .unwrap();
+        triangulation.insert_point(point);
+        triangulation.insert_point(point);
+        let vertex = triangulation.vertex_points.len() - 1;
+        assert_eq!(triangulation.vertex_points[vertex], point);
+        assert_eq!(triangulation.find_vertex(point), Some(vertex));
+        assert_eq!(
+            triangulation
+                .vertex_points
+                .iter()
+                .filter(|&&p| p == point)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit the stored index type")]
+    fn sentinel_index_is_rejected() {
+        to_index(usize::MAX);
+    }
+}
@@ -1,27 +1,33 @@
-use crate::Point;
+use std::cmp::Ordering;
 
-// TODO: consider `robust` library instead
-pub(crate) fn get_signed_area(point_a: Point, point_b: Point, point_c: Point) -> isize {
-    let r1 = point_b.0 as isize - point_c.0 as isize;
-    let r2 = point_a.1 as isize - point_c.1 as isize;
-    let r3 = point_b.1 as isize - point_c.1 as isize;
-    let r4 = point_a.0 as isize - point_c.0 as isize;
+use crate::{HeightValue, Point, Triangle};
+
+/// Twice the signed area of the triangle, positive for the winding of mesh triangles.
+/// Coordinates are widened to `i64`, which is exact for grids up to 2^30 on a side.
+pub(crate) fn get_signed_area(point_a: Point, point_b: Point, point_c: Point) -> i64 {
+    let r1 = point_b.0 as i64 - point_c.0 as i64;
+    let r2 = point_a.1 as i64 - point_c.1 as i64;
+    let r3 = point_b.1 as i64 - point_c.1 as i64;
+    let r4 = point_a.0 as i64 - point_c.0 as i64;
 
     r1 * r2 - r3 * r4
 }
 
+/// Checks whether the test point lies inside the circumcircle of the triangle with positive
+/// signed area. The determinant is evaluated in `i128` so it doesn't overflow on large grids.
+#[cfg(not(feature = "robust-predicates"))]
 pub(crate) fn is_point_in_circumcircle(
     test_point: Point,
     point_a: Point,
     point_b: Point,
     point_c: Point,
 ) -> bool {
-    let delta_x_a = point_a.0 as isize - test_point.0 as isize;
-    let delta_y_a = point_a.1 as isize - test_point.1 as isize;
-    let delta_x_b = point_b.0 as isize - test_point.0 as isize;
-    let delta_y_b = point_b.1 as isize - test_point.1 as isize;
-    let delta_x_c = point_c.0 as isize - test_point.0 as isize;
-    let delta_y_c = point_c.1 as isize - test_point.1 as isize;
+    let delta_x_a = point_a.0 as i128 - test_point.0 as i128;
+    let delta_y_a = point_a.1 as i128 - test_point.1 as i128;
+    let delta_x_b = point_b.0 as i128 - test_point.0 as i128;
+    let delta_y_b = point_b.1 as i128 - test_point.1 as i128;
+    let delta_x_c = point_c.0 as i128 - test_point.0 as i128;
+    let delta_y_c = point_c.1 as i128 - test_point.1 as i128;
 
     let square_distance_a = delta_x_a * delta_x_a + delta_y_a * delta_y_a;
     let square_distance_b = delta_x_b * delta_x_b + delta_y_b * delta_y_b;
@@ -32,3 +38,274 @@ pub(crate) fn is_point_in_circumcircle(
         + square_distance_a * (delta_x_b * delta_y_c - delta_y_b * delta_x_c)
         < 0
 }
+
+/// Checks whether the test point lies inside the circumcircle of the triangle with positive
+/// signed area, using exact arithmetic on `f64` coordinates.
+#[cfg(feature = "robust-predicates")]
+pub(crate) fn is_point_in_circumcircle(
+    test_point: Point,
+    point_a: Point,
+    point_b: Point,
+    point_c: Point,
+) -> bool {
+    robust::incircle(
+        to_coord(point_a),
+        to_coord(point_b),
+        to_coord(point_c),
+        to_coord(test_point),
+    ) < 0.0
+}
+
+/// Checks whether the test point lies inside the circumcircle of the triangle with positive
+/// signed area, with coordinates scaled by the cell size. Evaluated in `f64`.
+pub(crate) fn is_point_in_scaled_circumcircle(
+    test_point: Point,
+    point_a: Point,
+    point_b: Point,
+    point_c: Point,
+    cell_size: (f64, f64),
+) -> bool {
+    scaled_circumcircle_determinant(test_point, point_a, point_b, point_c, cell_size) < 0.0
+}
+
+/// Determinant of the circumcircle test evaluated in `f64`, negative when the test point lies
+/// inside the circumcircle of the triangle with positive signed area.
+#[cfg(not(feature = "robust-predicates"))]
+pub(crate) fn circumcircle_determinant(
+    test_point: Point,
+    point_a: Point,
+    point_b: Point,
+    point_c: Point,
+) -> f64 {
+    scaled_circumcircle_determinant(test_point, point_a, point_b, point_c, (1.0, 1.0))
+}
+
+/// Determinant of the circumcircle test, negative when the test point lies inside the
+/// circumcircle of the triangle with positive signed area. Its sign is exact.
+#[cfg(feature = "robust-predicates")]
+pub(crate) fn circumcircle_determinant(
+    test_point: Point,
+    point_a: Point,
+    point_b: Point,
+    point_c: Point,
+) -> f64 {
+    robust::incircle(
+        to_coord(point_a),
+        to_coord(point_b),
+        to_coord(point_c),
+        to_coord(test_point),
+    )
+}
+
+/// Determinant of the circumcircle test with coordinates scaled by the cell size, negative
+/// when the test point lies inside the circumcircle of the triangle with positive signed area.
+pub(crate) fn scaled_circumcircle_determinant(
+    test_point: Point,
+    point_a: Point,
+    point_b: Point,
+    point_c: Point,
+    cell_size: (f64, f64),
+) -> f64 {
+    // scaling x and y multiplies the determinant by a positive factor, so only squared
+    // distances need the scale
+    let weight_x = cell_size.0 * cell_size.0;
+    let weight_y = cell_size.1 * cell_size.1;
+    let [delta_a, delta_b, delta_c] = [point_a, point_b, point_c].map(|point| {
+        let delta_x = point.0 as f64 - test_point.0 as f64;
+        let delta_y = point.1 as f64 - test_point.1 as f64;
+
+        (
+            delta_x,
+            delta_y,
+            weight_x * delta_x * delta_x + weight_y * delta_y * delta_y,
+        )
+    });
+
+    delta_a.0 * (delta_b.1 * delta_c.2 - delta_b.2 * delta_c.1)
+        - delta_a.1 * (delta_b.0 * delta_c.2 - delta_b.2 * delta_c.0)
+        + delta_a.2 * (delta_b.0 * delta_c.1 - delta_b.1 * delta_c.0)
+}
+
+/// Checks whether the height is the nodata sentinel, `NaN` sentinel matches any `NaN`.
+pub(crate) fn is_nodata_value(height: f64, nodata: f64) -> bool {
+    height == nodata || (nodata.is_nan() && height.is_nan())
+}
+
+/// Orientation of the triangle, `Greater` for positive signed area like triangles of the mesh
+/// and `Equal` for collinear points.
+#[cfg(not(feature = "robust-predicates"))]
+pub(crate) fn orientation(point_a: Point, point_b: Point, point_c: Point) -> Ordering {
+    get_signed_area(point_a, point_b, point_c).cmp(&0)
+}
+
+/// Orientation of the triangle, `Greater` for positive signed area like triangles of the mesh
+/// and `Equal` for collinear points, using exact arithmetic on `f64` coordinates.
+#[cfg(feature = "robust-predicates")]
+pub(crate) fn orientation(point_a: Point, point_b: Point, point_c: Point) -> Ordering {
+    // `orient2d` is positive for counterclockwise points, which is opposite to `get_signed_area`
+    let determinant = robust::orient2d(to_coord(point_a), to_coord(point_b), to_coord(point_c));
+    0.0.partial_cmp(&determinant).unwrap_or(Ordering::Equal)
+}
+
+#[cfg(feature = "robust-predicates")]
+fn to_coord(point: Point) -> robust::Coord<f64> {
+    robust::Coord {
+        x: point.0 as f64,
+        y: point.1 as f64,
+    }
+}
+
+/// Barycentric weights of the triangle vertices at the point, which may lie between pixels.
+/// Weights are negative outside of the triangle. Degenerate triangles have no weights.
+pub(crate) fn barycentric_weights(
+    point_a: Point,
+    point_b: Point,
+    point_c: Point,
+    point: (f64, f64),
+) -> Option<(f64, f64, f64)> {
+    // same orientation as `get_signed_area`, but in floating point
+    let signed_area = |a: (f64, f64), b: (f64, f64), c: (f64, f64)| {
+        (b.0 - c.0) * (a.1 - c.1) - (b.1 - c.1) * (a.0 - c.0)
+    };
+    let [a, b, c] = [point_a, point_b, point_c].map(|point| (point.0 as f64, point.1 as f64));
+
+    let area = signed_area(a, b, c);
+    if area == 0.0 {
+        return None;
+    }
+
+    Some((
+        signed_area(b, c, point) / area,
+        signed_area(c, a, point) / area,
+        signed_area(a, b, point) / area,
+    ))
+}
+
+/// Calls `f` for every pixel inside the triangle (including its edges) with barycentric
+/// weights of the triangle vertices at that pixel. Degenerate triangles have no pixels.
+pub(crate) fn for_each_triangle_pixel(
+    point_a: Point,
+    point_b: Point,
+    point_c: Point,
+    mut f: impl FnMut(Point, (f64, f64, f64)),
+) {
+    let min_x = point_a.0.min(point_b.0).min(point_c.0);
+    let min_y = point_a.1.min(point_b.1).min(point_c.1);
+    let max_x = point_a.0.max(point_b.0).max(point_c.0);
+    let max_y = point_a.1.max(point_b.1).max(point_c.1);
+
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            // weights are all non-negative only for pixels inside
+            let Some(weights) =
+                barycentric_weights(point_a, point_b, point_c, (x as f64, y as f64))
+            else {
+                return;
+            };
+            if weights.0 >= 0.0 && weights.1 >= 0.0 && weights.2 >= 0.0 {
+                f((x, y), weights);
+            }
+        }
+    }
+}
+
+/// Computes normal of every vertex by averaging normals of its triangles weighted by their area.
+/// Normals point up (positive z), vertices without triangles get zero normal.
+pub(crate) fn compute_vertex_normals<H: HeightValue>(
+    points: &[Point],
+    triangles: &[Triangle],
+    height_data: &[H],
+    width: usize,
+) -> Vec<[f32; 3]> {
+    let mut normals = vec![[0.0f64; 3]; points.len()];
+
+    for &(a, b, c) in triangles {
+        let [point_a, point_b, point_c] = [a, b, c].map(|vertex| {
+            let point = points[vertex];
+            [
+                point.0 as f64,
+                point.1 as f64,
+                height_data[point.1 * width + point.0].to_f64(),
+            ]
+        });
+        let ab = [0, 1, 2].map(|i| point_b[i] - point_a[i]);
+        let ac = [0, 1, 2].map(|i| point_c[i] - point_a[i]);
+        // length of the cross product is twice the area, so normals are area weighted
+        let mut face_normal = [
+            ab[1] * ac[2] - ab[2] * ac[1],
+            ab[2] * ac[0] - ab[0] * ac[2],
+            ab[0] * ac[1] - ab[1] * ac[0],
+        ];
+        if face_normal[2] < 0.0 {
+            face_normal = face_normal.map(|value| -value);
+        }
+
+        for vertex in [a, b, c] {
+            for i in 0..3 {
+                normals[vertex][i] += face_normal[i];
+            }
+        }
+    }
+
+    normals
+        .into_iter()
+        .map(|normal| {
+            let length =
+                (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+            if length == 0.0 {
+                return [0.0; 3];
+            }
+
+            normal.map(|value| (value / length) as f32)
+        })
+        .collect()
+}
+
+/// Deterministic pseudo-random value of the point for the seed (SplitMix64 finalizer).
+pub(crate) fn tie_break_hash(point: Point, seed: u64) -> u64 {
+    let mut hash = seed
+        ^ (point.0 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (point.1 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+    hash ^ (hash >> 31)
+}
+
+/// Finds pixels strictly higher or strictly lower than all of their 8 neighbours, with rows
+/// `stride` apart in the height data. Pixels on the border of the grid are compared only with
+/// neighbours inside the grid.
+pub(crate) fn find_local_extrema<H: HeightValue>(
+    height_data: &[H],
+    width: usize,
+    height: usize,
+    stride: usize,
+) -> Vec<Point> {
+    let mut extrema = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let value = height_data[y * stride + x].to_f64();
+            let mut is_maximum = true;
+            let mut is_minimum = true;
+
+            for neighbour_y in y.saturating_sub(1)..=(y + 1).min(height - 1) {
+                for neighbour_x in x.saturating_sub(1)..=(x + 1).min(width - 1) {
+                    if (neighbour_x, neighbour_y) == (x, y) {
+                        continue;
+                    }
+
+                    let neighbour_value = height_data[neighbour_y * stride + neighbour_x].to_f64();
+                    is_maximum &= value > neighbour_value;
+                    is_minimum &= value < neighbour_value;
+                }
+            }
+
+            if is_maximum || is_minimum {
+                extrema.push((x, y));
+            }
+        }
+    }
+
+    extrema
+}
@@ -0,0 +1,94 @@
+/*!
+Bindings for running the triangulation in the browser, enabled by the `wasm` feature.
+
+Results are flat arrays, which become `Uint32Array`s in JavaScript and can be uploaded to
+WebGL buffers as they are.
+*/
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Error, TriangulationOptions};
+
+/// Result of [`triangulate_wasm`] as flat arrays.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WasmMesh {
+    points: Vec<u32>,
+    triangles: Vec<u32>,
+}
+
+#[wasm_bindgen]
+impl WasmMesh {
+    /// Vertices as `x0, y0, x1, y1, ...` grid coordinates.
+    #[wasm_bindgen(getter)]
+    pub fn points(&self) -> Vec<u32> {
+        self.points.clone()
+    }
+
+    /// Triangles as `a0, b0, c0, a1, b1, c1, ...` indices into the vertices.
+    #[wasm_bindgen(getter)]
+    pub fn triangles(&self) -> Vec<u32> {
+        self.triangles.clone()
+    }
+
+    /// Number of vertices, half the length of `points`.
+    #[wasm_bindgen(getter, js_name = pointCount)]
+    pub fn point_count(&self) -> usize {
+        self.points.len() / 2
+    }
+
+    /// Number of triangles, a third of the length of `triangles`.
+    #[wasm_bindgen(getter, js_name = triangleCount)]
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len() / 3
+    }
+}
+
+/// Runs the triangulation process until the maximum error is below the specified threshold.
+///
+/// Same as [`triangulate`](crate::triangulate), exported to JavaScript as `triangulate`.
+///
+/// # Arguments
+///
+/// * `heights` - Height values of the grid, e.g. a `Float64Array` decoded from canvas `ImageData`.
+/// * `width` - The width of the grid.
+/// * `height` - The height of the grid.
+/// * `max_error` - The maximum allowable error for the triangulation process.
+///
+/// # Errors
+///
+/// Message of the [`TriangulationError`](crate::TriangulationError), thrown in JavaScript.
+///
+/// # Example
+///
+/// ```rust
+/// use delatin::wasm::triangulate_wasm;
+///
+/// let heights = vec![100.1, 123.4, 111.5, 121.4];
+/// let mesh = triangulate_wasm(&heights, 2, 2, 1.0).unwrap();
+/// assert_eq!(mesh.points(), vec![0, 0, 1, 0, 1, 1, 0, 1]);
+/// assert_eq!(mesh.triangle_count(), 2);
+/// ```
+#[wasm_bindgen(js_name = triangulate)]
+pub fn triangulate_wasm(
+    heights: &[f64],
+    width: usize,
+    height: usize,
+    max_error: f64,
+) -> Result<WasmMesh, JsError> {
+    let (points, triangles) = TriangulationOptions::new()
+        .max_error(Error(max_error))
+        .run(heights, width, height)
+        .map_err(|error| JsError::new(&error.to_string()))?;
+
+    Ok(WasmMesh {
+        points: points
+            .into_iter()
+            .flat_map(|(x, y)| [x as u32, y as u32])
+            .collect(),
+        triangles: triangles
+            .into_iter()
+            .flat_map(|(a, b, c)| [a as u32, b as u32, c as u32])
+            .collect(),
+    })
+}
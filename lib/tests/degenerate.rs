@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use delatin::predicates::signed_area;
+use delatin::{Error, Mesh, TriangulationOptions};
+
+/// Edges used by a single triangle, which are cracks unless they are on the outline.
+fn open_edges(mesh: &Mesh) -> Vec<((usize, usize), (usize, usize))> {
+    let mut edges = HashMap::new();
+    for (a, b, c) in mesh.triangles() {
+        let [a, b, c] = [a, b, c].map(|vertex| mesh.points()[vertex]);
+        for (start, end) in [(a, b), (b, c), (c, a)] {
+            *edges.entry((start.min(end), start.max(end))).or_insert(0) += 1;
+        }
+    }
+
+    edges
+        .into_iter()
+        .filter(|&(_, count)| count == 1)
+        .map(|(edge, _)| edge)
+        .collect()
+}
+
+#[test]
+fn adjacent_degenerate_triangles_are_collapsed_without_cracks() {
+    // square split by a horizontal line with two slivers along it, the first sliver's
+    // longest edge is shared with the second one
+    let points = vec![
+        (0, 2),
+        (1, 2),
+        (2, 2),
+        (4, 2),
+        (0, 0),
+        (4, 0),
+        (4, 4),
+        (0, 4),
+    ];
+    let triangles = [
+        (0, 3, 5),
+        (0, 5, 4),
+        (2, 0, 1),
+        (3, 0, 2),
+        (1, 0, 7),
+        (2, 1, 7),
+        (2, 7, 6),
+        (3, 2, 6),
+    ];
+    let mesh = Mesh::from_triangles(points, &triangles);
+
+    let collapsed = mesh.collapse_degenerate();
+
+    assert_eq!(collapsed.validate(), Ok(()));
+    assert_eq!(collapsed.triangles().len(), triangles.len());
+    let is_on_outline = |(start, end): ((usize, usize), (usize, usize))| {
+        (start.0 == end.0 && start.0 % 4 == 0) || (start.1 == end.1 && start.1 % 4 == 0)
+    };
+    assert!(open_edges(&collapsed).into_iter().all(is_on_outline));
+}
+
+#[test]
+fn degenerate_triangles_waiting_for_each_other_are_dropped() {
+    // both slivers have the longest edge from (0, 0) to (4, 0)
+    let points = vec![(0, 0), (4, 0), (1, 0), (3, 0)];
+    let mesh = Mesh::from_triangles(points, &[(0, 1, 2), (1, 0, 3)]);
+
+    assert!(mesh.collapse_degenerate().triangles().is_empty());
+}
+
+#[test]
+fn grid_with_ridge_on_the_diagonal_has_no_degenerate_triangles() {
+    // every point of the ridge lies on the edge shared by the first two triangles, and
+    // scaled cells and tolerance compare near-cocircular points in floating point
+    let heights = (0..9 * 9)
+        .map(|i| match (i % 9, i / 9) {
+            (x, y) if x == y && 0 < x && x < 8 => 10.0,
+            (x, y) => ((x * 7 + y * 3) % 5) as f64 * 0.1,
+        })
+        .collect::<Vec<f64>>();
+
+    for (cell_size, tolerance) in [((1.0, 1.0), 0.0), ((1.0, 3.0), 0.0), ((0.3, 1.0), 1e-9)] {
+        let mesh = TriangulationOptions::new()
+            .max_error(Error(0.0))
+            .cell_size(cell_size)
+            .circumcircle_tolerance(tolerance)
+            .run_mesh(&heights, 9, 9)
+            .unwrap();
+
+        assert_eq!(mesh.validate(), Ok(()));
+        for (a, b, c) in mesh.triangles() {
+            let [a, b, c] = [a, b, c].map(|vertex| mesh.points()[vertex]);
+            assert!(signed_area(a, b, c) > 0);
+        }
+        assert_eq!(mesh.collapse_degenerate().triangles(), mesh.triangles());
+    }
+}
+
+#[test]
+fn stretched_cells_flip_only_convex_quads() {
+    // flips are checked with debug assertions, circles of the Delaunay test get very flat
+    let heights = (0..65 * 65)
+        .map(|i: usize| (i.wrapping_mul(2_654_435_761) % 1000) as f64)
+        .collect::<Vec<f64>>();
+
+    for (cell_size, tolerance) in [
+        ((1.0, 1000.0), 0.0),
+        ((1e-3, 1.0), 1e-12),
+        ((7.0, 0.01), 0.0),
+    ] {
+        let mesh = TriangulationOptions::new()
+            .max_error(Error(0.0))
+            .cell_size(cell_size)
+            .circumcircle_tolerance(tolerance)
+            .run_mesh(&heights, 65, 65)
+            .unwrap();
+
+        assert_eq!(mesh.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,195 @@
+use delatin::export::{write_obj_with_options, write_ply, write_stl_binary, ObjOptions};
+use delatin::{triangulate, Error, TriangulationOptions};
+
+const WIDTH: usize = 9;
+
+type Points = Vec<(usize, usize)>;
+type Triangles = Vec<(usize, usize, usize)>;
+
+/// Heights in halves, exact in `f32` also after adding the offset.
+fn grid() -> (Vec<f64>, Points, Triangles) {
+    let heights = (0..WIDTH * WIDTH)
+        .map(|i| ((i * 7) % 13) as f64 * 0.5)
+        .collect::<Vec<f64>>();
+    let (points, triangles) = triangulate(&heights, (WIDTH, WIDTH), Error(0.5)).unwrap();
+
+    (heights, points, triangles)
+}
+
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[test]
+fn obj_z_offset_raises_heights() {
+    let (heights, points, triangles) = grid();
+    let vertex_heights = |z_offset: f64| {
+        let mut obj = Vec::new();
+        let options = ObjOptions {
+            z_offset,
+            ..Default::default()
+        };
+        write_obj_with_options(&mut obj, &points, &triangles, &heights, WIDTH, &options).unwrap();
+        String::from_utf8(obj)
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.strip_prefix("v "))
+            .map(|line| line.split(' ').nth(2).unwrap().parse::<f64>().unwrap())
+            .collect::<Vec<f64>>()
+    };
+
+    let original = vertex_heights(0.0);
+    let shifted = vertex_heights(100.0);
+    assert_eq!(original.len(), points.len());
+    for (original, shifted) in original.iter().zip(&shifted) {
+        assert_eq!(*shifted, original + 100.0);
+    }
+}
+
+#[test]
+fn obj_uv_tile_maps_into_atlas_cell() {
+    let (heights, points, triangles) = grid();
+    let uvs = |flip_v: bool| {
+        let mut obj = Vec::new();
+        let options = ObjOptions {
+            uvs: true,
+            flip_v,
+            // column 1 and row 0 of a 2x2 atlas
+            uv_tile: Some((2, 2, 1)),
+            ..Default::default()
+        };
+        write_obj_with_options(&mut obj, &points, &triangles, &heights, WIDTH, &options).unwrap();
+        String::from_utf8(obj)
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.strip_prefix("vt "))
+            .map(|line| {
+                let (u, v) = line.split_once(' ').unwrap();
+                (u.parse::<f64>().unwrap(), v.parse::<f64>().unwrap())
+            })
+            .collect::<Vec<(f64, f64)>>()
+    };
+    let corner_uv = |uvs: &[(f64, f64)], corner: (usize, usize)| {
+        uvs[points.iter().position(|&point| point == corner).unwrap()]
+    };
+
+    let top_right = uvs(false);
+    assert_eq!(top_right.len(), points.len());
+    assert!(top_right
+        .iter()
+        .all(|&(u, v)| (0.5..=1.0).contains(&u) && (0.0..=0.5).contains(&v)));
+    assert_eq!(corner_uv(&top_right, (0, 0)), (0.5, 0.0));
+    assert_eq!(corner_uv(&top_right, (WIDTH - 1, WIDTH - 1)), (1.0, 0.5));
+
+    // bottom-left origin keeps the cell at the top of the texture
+    let flipped = uvs(true);
+    assert!(flipped
+        .iter()
+        .all(|&(u, v)| (0.5..=1.0).contains(&u) && (0.5..=1.0).contains(&v)));
+    assert_eq!(corner_uv(&flipped, (0, 0)), (0.5, 1.0));
+    assert_eq!(corner_uv(&flipped, (WIDTH - 1, WIDTH - 1)), (1.0, 0.5));
+}
+
+#[test]
+fn ply_z_offset_raises_heights() {
+    let (heights, points, triangles) = grid();
+    let vertex_heights = |z_offset: f64| {
+        let mut ply = Vec::new();
+        write_ply(
+            &mut ply, &points, &triangles, &heights, WIDTH, false, z_offset,
+        )
+        .unwrap();
+        let start = ply
+            .windows(11)
+            .position(|window| window == b"end_header\n")
+            .unwrap()
+            + 11;
+        (0..points.len())
+            .map(|vertex| read_f32(&ply, start + vertex * 12 + 8))
+            .collect::<Vec<f32>>()
+    };
+
+    for (original, shifted) in vertex_heights(0.0).iter().zip(&vertex_heights(100.0)) {
+        assert_eq!(*shifted, original + 100.0);
+    }
+}
+
+#[test]
+fn stl_z_offset_raises_heights() {
+    let (heights, points, triangles) = grid();
+    let records = |z_offset: f64| {
+        let mut stl = Vec::new();
+        write_stl_binary(&mut stl, &points, &triangles, &heights, WIDTH, z_offset).unwrap();
+        stl[84..].to_vec()
+    };
+
+    let original = records(0.0);
+    let shifted = records(100.0);
+    assert_eq!(original.len(), triangles.len() * 50);
+    for (original, shifted) in original.chunks_exact(50).zip(shifted.chunks_exact(50)) {
+        // normal is unchanged, z of the three vertices is raised
+        for offset in [0, 4, 8] {
+            assert_eq!(read_f32(shifted, offset), read_f32(original, offset));
+        }
+        for offset in [20, 32, 44] {
+            assert_eq!(
+                read_f32(shifted, offset),
+                read_f32(original, offset) + 100.0
+            );
+        }
+    }
+}
+
+#[cfg(feature = "gltf")]
+#[test]
+fn gltf_z_offset_raises_heights() {
+    use delatin::export::write_gltf;
+
+    let (heights, points, triangles) = grid();
+    let vertex_heights = |z_offset: f64| {
+        let glb = write_gltf(&points, &triangles, &heights, WIDTH, z_offset);
+        let json_length = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        let binary_start = 20 + json_length + 8;
+        (0..points.len())
+            .map(|vertex| read_f32(&glb, binary_start + vertex * 12 + 4))
+            .collect::<Vec<f32>>()
+    };
+
+    for (original, shifted) in vertex_heights(0.0).iter().zip(&vertex_heights(100.0)) {
+        assert_eq!(*shifted, original + 100.0);
+    }
+}
+
+#[test]
+fn u16_grid_reads_like_f64_grid() {
+    let heights = (0..WIDTH * WIDTH)
+        .map(|i| ((i * 7) % 13) as u16)
+        .collect::<Vec<u16>>();
+    let copied = heights
+        .iter()
+        .map(|&height| height as f64)
+        .collect::<Vec<f64>>();
+    let mesh = TriangulationOptions::new()
+        .max_error(Error(1.0))
+        .run_mesh(&heights, WIDTH, WIDTH)
+        .unwrap();
+
+    let (mut stl, mut copied_stl) = (Vec::new(), Vec::new());
+    mesh.write_stl_binary(&mut stl, &heights, WIDTH).unwrap();
+    mesh.write_stl_binary(&mut copied_stl, &copied, WIDTH)
+        .unwrap();
+    assert_eq!(stl, copied_stl);
+    assert_eq!(
+        mesh.vertex_normals(&heights, WIDTH),
+        mesh.vertex_normals(&copied, WIDTH)
+    );
+    assert_eq!(
+        mesh.sample(&heights, WIDTH, 2.5, 3.25),
+        mesh.sample(&copied, WIDTH, 2.5, 3.25)
+    );
+    assert!(mesh.verify_consistency(&heights, WIDTH));
+    assert_eq!(
+        mesh.merge_coplanar(&heights, WIDTH, 0.5),
+        mesh.merge_coplanar(&copied, WIDTH, 0.5)
+    );
+}
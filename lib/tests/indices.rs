@@ -0,0 +1,82 @@
+//! Run both with and without the `u32-indices` feature, meshes have to be the same.
+//! Fingerprints are those of the first release, so changes of rounding show up here too.
+
+use delatin::{triangulate, Error, TriangulationError};
+
+/// FNV-1a hash of points and triangles, stable across builds.
+fn fingerprint(points: &[(usize, usize)], triangles: &[(usize, usize, usize)]) -> u64 {
+    let values = points
+        .iter()
+        .flat_map(|&(x, y)| [x, y])
+        .chain(triangles.iter().flat_map(|&(a, b, c)| [a, b, c]));
+
+    values.fold(0xcbf2_9ce4_8422_2325, |hash, value| {
+        (value as u64)
+            .to_le_bytes()
+            .iter()
+            .fold(hash, |hash, &byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+            })
+    })
+}
+
+#[test]
+fn mesh_is_independent_of_index_type() {
+    let size = 129;
+    let heights = (0..size * size)
+        .map(|i| ((i % size) as f64 / 7.0).sin() * ((i / size) as f64 / 5.0).cos() * 100.0)
+        .collect::<Vec<f64>>();
+    let (points, triangles) = triangulate(&heights, (size, size), Error(0.5)).unwrap();
+
+    assert_eq!(
+        (
+            points.len(),
+            triangles.len(),
+            fingerprint(&points, &triangles)
+        ),
+        (11940, 23571, 10643196073460940858)
+    );
+}
+
+#[test]
+fn fixture_mesh_is_unchanged() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/input.json");
+    let file = std::fs::File::open(path).unwrap();
+    let heights: Vec<f64> = serde_json::from_reader(file).unwrap();
+    let (points, triangles) = triangulate(&heights, (512, 512), Error(0.2)).unwrap();
+
+    assert_eq!(
+        (
+            points.len(),
+            triangles.len(),
+            fingerprint(&points, &triangles)
+        ),
+        (16257, 32147, 7833115864583074288)
+    );
+}
+
+#[test]
+fn oversized_grid_is_rejected() {
+    let result = triangulate(&[], (usize::MAX, 2), Error(1.0));
+    assert!(matches!(
+        result,
+        Err(TriangulationError::GridTooLarge {
+            width: usize::MAX,
+            height: 2
+        })
+    ));
+
+    // fits `usize` indices, but not `u32` ones
+    let result = triangulate(&[], (70_000, 70_000), Error(1.0));
+    if cfg!(feature = "u32-indices") {
+        assert!(matches!(
+            result,
+            Err(TriangulationError::GridTooLarge { .. })
+        ));
+    } else {
+        assert!(matches!(
+            result,
+            Err(TriangulationError::InvalidDataLengthError)
+        ));
+    }
+}
@@ -0,0 +1,49 @@
+use delatin::{triangulate_mesh, Error, TriangulationOptions};
+
+/// Stack far smaller than the default one, but with room for the frames of the exact predicates
+/// of the `robust-predicates` feature. Recursive legalization overflowed it in debug builds.
+const STACK_SIZE: usize = 64 * 1024;
+
+#[test]
+fn exact_noise_triangulation_fits_a_small_stack() {
+    // refining noise until exact makes many chains of flips
+    let size = 513;
+    let noise = (0..size * size)
+        .map(|i: usize| (i.wrapping_mul(2_654_435_761) % 1000) as f64)
+        .collect::<Vec<f64>>();
+
+    let mesh = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || triangulate_mesh(&noise, (size, size), Error(0.0)).unwrap())
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(mesh.max_error(), Error(0.0));
+    assert!(mesh.points().len() > size * size / 4);
+}
+
+#[test]
+fn long_flip_chain_fits_a_small_stack() {
+    // the last seed point lies inside the circumcircles of all triangles below the row of
+    // seed points, so its insertion flips about a thousand edges, each found by the previous flip
+    let (width, height) = (4097, 5);
+    let heights = vec![0.0; width * height];
+    let mut seed_points = (1..width - 1).map(|x| (x, 1)).collect::<Vec<_>>();
+    seed_points.push((width / 2, height - 2));
+
+    let (points, _) = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            TriangulationOptions::new()
+                .max_error(Error(1.0))
+                .seed_points(&seed_points)
+                .run(&heights, width, height)
+                .unwrap()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(points.len(), width + 3);
+}
@@ -0,0 +1,79 @@
+use delatin::predicates::signed_area;
+use delatin::{triangulate_regions, Error, TriangulationOptions};
+
+const SIZE: usize = 65;
+
+/// Sloping plane with round voids in the middle and at the top-left corner.
+fn plane_with_voids() -> Vec<f64> {
+    let center = (SIZE / 2) as f64;
+    (0..SIZE * SIZE)
+        .map(|i| {
+            let (x, y) = ((i % SIZE) as f64, (i / SIZE) as f64);
+            let in_hole = (x - center).hypot(y - center) < SIZE as f64 / 4.0;
+            if in_hole || x.hypot(y) < SIZE as f64 / 3.0 {
+                f64::NAN
+            } else {
+                x * 0.5 + y * 0.25
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn triangles_touching_voids_are_measured_from_valid_vertices() {
+    let heights = plane_with_voids();
+    let mesh = TriangulationOptions::new()
+        .max_error(Error(0.1))
+        .nodata(f64::NAN)
+        .run_mesh(&heights, SIZE, SIZE)
+        .unwrap();
+
+    // the void corner is a vertex, but valid vertices predict the plane around it
+    assert!(mesh.max_error().0 <= 0.1);
+    assert!(mesh.points().len() < 10);
+}
+
+#[test]
+fn regions_cover_every_valid_pixel() {
+    let heights = plane_with_voids();
+    let regions = triangulate_regions(&heights, (SIZE, SIZE), Error(1.0), f64::NAN).unwrap();
+
+    let covers = |point| {
+        regions.iter().any(|region| {
+            region.triangles().iter().any(|&(a, b, c)| {
+                let [a, b, c] = [a, b, c].map(|vertex| region.points()[vertex]);
+                signed_area(a, b, point) >= 0
+                    && signed_area(b, c, point) >= 0
+                    && signed_area(c, a, point) >= 0
+            })
+        })
+    };
+    for (i, height) in heights.iter().enumerate() {
+        if !height.is_nan() {
+            assert!(covers((i % SIZE, i / SIZE)), "pixel {i} is not covered");
+        }
+    }
+}
+
+#[test]
+fn remove_nodata_reads_strided_data_of_any_height_type() {
+    let heights = plane_with_voids();
+    let mesh = TriangulationOptions::new()
+        .max_error(Error(0.1))
+        .nodata(f64::NAN)
+        .run_mesh(&heights, SIZE, SIZE)
+        .unwrap();
+
+    let stride = SIZE + 3;
+    let padded = (0..SIZE * stride)
+        .map(|i| match i % stride {
+            x if x < SIZE => heights[i / stride * SIZE + x] as f32,
+            _ => 0.0,
+        })
+        .collect::<Vec<f32>>();
+
+    assert_eq!(
+        mesh.remove_nodata(&padded, stride, f64::NAN).triangles(),
+        mesh.remove_nodata(&heights, SIZE, f64::NAN).triangles()
+    );
+}
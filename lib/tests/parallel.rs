@@ -0,0 +1,56 @@
+use delatin::{triangulate_mesh, Error};
+
+fn waves(size: usize) -> Vec<f64> {
+    (0..size * size)
+        .map(|i| ((i % size) as f64 / 7.0).sin() * ((i / size) as f64 / 5.0).cos() * 100.0)
+        .collect()
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_mesh_matches_serial_one() {
+    // large enough to rasterize the first refinement steps in parallel
+    let heights = waves(257);
+    let triangulate_on = |threads: usize| {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(|| triangulate_mesh(&heights, (257, 257), Error(0.5)).unwrap())
+    };
+
+    let serial = triangulate_on(1);
+    for threads in [2, 4, 7] {
+        assert_eq!(triangulate_on(threads), serial);
+    }
+}
+
+/// Height which can't be shared between threads.
+#[cfg(not(feature = "rayon"))]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+struct LocalHeight(f64, std::marker::PhantomData<*const ()>);
+
+#[cfg(not(feature = "rayon"))]
+impl delatin::HeightValue for LocalHeight {
+    fn to_f64(self) -> f64 {
+        self.0
+    }
+}
+
+#[cfg(not(feature = "rayon"))]
+#[test]
+fn heights_need_not_be_sync_without_rayon() {
+    let heights = waves(33);
+    let local_heights = heights
+        .iter()
+        .map(|&height| LocalHeight(height, std::marker::PhantomData))
+        .collect::<Vec<LocalHeight>>();
+
+    let (points, triangles) = delatin::TriangulationOptions::new()
+        .max_error(Error(0.5))
+        .run(&local_heights, 33, 33)
+        .unwrap();
+    let mesh = triangulate_mesh(&heights, (33, 33), Error(0.5)).unwrap();
+    assert_eq!(points, mesh.points());
+    assert_eq!(triangles, mesh.triangles());
+}
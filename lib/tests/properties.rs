@@ -0,0 +1,146 @@
+use delatin::predicates::signed_area;
+use delatin::{triangulate, triangulate_mesh, triangulate_region, Error, Mesh};
+use proptest::prelude::*;
+
+/// Tolerance of interpolated heights, which are weighted sums of vertex heights.
+const TOLERANCE: f64 = 1e-9;
+
+/// Small grid with heights anywhere in a range, and a max error.
+fn grids() -> impl Strategy<Value = (Vec<f64>, (usize, usize), f64)> {
+    (2..=17usize, 2..=17usize, 0.0..50.0f64).prop_flat_map(|(width, height, max_error)| {
+        (
+            prop::collection::vec(-1000.0..1000.0f64, width * height),
+            Just((width, height)),
+            Just(max_error),
+        )
+    })
+}
+
+/// Small grid with few distinct integer heights, which makes plateaus, equal errors
+/// and cocircular candidates, and a max error.
+fn terraced_grids() -> impl Strategy<Value = (Vec<f64>, (usize, usize), f64)> {
+    (2..=17usize, 2..=17usize, 0..3u8).prop_flat_map(|(width, height, max_error)| {
+        (
+            prop::collection::vec((0..4i32).prop_map(f64::from), width * height),
+            Just((width, height)),
+            Just(f64::from(max_error)),
+        )
+    })
+}
+
+fn check_invariants(
+    heights: &[f64],
+    dimesions: (usize, usize),
+    max_error: f64,
+) -> Result<(), TestCaseError> {
+    let (width, height) = dimesions;
+    let mesh = triangulate_mesh(heights, dimesions, Error(max_error)).unwrap();
+
+    let corners = [
+        (0, 0),
+        (width - 1, 0),
+        (0, height - 1),
+        (width - 1, height - 1),
+    ];
+    for corner in corners {
+        prop_assert!(
+            mesh.points().contains(&corner),
+            "missing corner {:?}",
+            corner
+        );
+    }
+
+    // positive area, vertex range and twin consistency
+    prop_assert_eq!(mesh.validate(), Ok(()));
+
+    for (a, b, c) in mesh.iter_triangles() {
+        let [a, b, c] = [a, b, c].map(|vertex| mesh.points()[vertex]);
+        prop_assert!(
+            signed_area(a, b, c) > 0,
+            "{:?} is not counterclockwise",
+            (a, b, c)
+        );
+    }
+    prop_assert_eq!(mesh.ensure_ccw().triangles(), mesh.triangles());
+
+    let half_edges = mesh.half_edges();
+    for (half_edge, twin) in half_edges.iter().enumerate() {
+        if let Some(twin) = *twin {
+            prop_assert_eq!(half_edges[twin], Some(half_edge));
+        }
+    }
+
+    for &(x, y) in mesh.points() {
+        let sampled = mesh.sample(heights, width, x as f64, y as f64);
+        let expected = heights[y * width + x];
+        prop_assert!(
+            sampled.is_some_and(|sampled| (sampled - expected).abs() <= TOLERANCE),
+            "vertex {:?} sampled as {:?} instead of {}",
+            (x, y),
+            sampled,
+            expected
+        );
+    }
+
+    prop_assert!(mesh.max_error().0 <= max_error);
+    for y in 0..height {
+        for x in 0..width {
+            let sampled = mesh.sample(heights, width, x as f64, y as f64);
+            let expected = heights[y * width + x];
+            prop_assert!(
+                sampled.is_some_and(|sampled| (sampled - expected).abs() <= max_error + TOLERANCE),
+                "pixel {:?} sampled as {:?} instead of {}",
+                (x, y),
+                sampled,
+                expected
+            );
+        }
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn mesh_is_valid((heights, dimesions, max_error) in grids()) {
+        check_invariants(&heights, dimesions, max_error)?;
+    }
+
+    #[test]
+    fn terraced_mesh_is_valid((heights, dimesions, max_error) in terraced_grids()) {
+        check_invariants(&heights, dimesions, max_error)?;
+    }
+
+    #[test]
+    fn rebuilt_mesh_is_valid((heights, dimesions, max_error) in grids()) {
+        let mesh = triangulate_mesh(&heights, dimesions, Error(max_error)).unwrap();
+        let rebuilt = Mesh::from_triangles(mesh.points().to_vec(), &mesh.triangles());
+
+        prop_assert_eq!(rebuilt.validate(), Ok(()));
+        prop_assert_eq!(rebuilt.half_edges(), mesh.half_edges());
+    }
+
+    #[test]
+    fn region_matches_copied_grid(
+        ((heights, dimesions, max_error), corners) in grids().prop_flat_map(|grid| {
+            let (width, height) = grid.1;
+            let corners = (0..width - 1, 0..height - 1).prop_flat_map(move |(min_x, min_y)| {
+                (Just(min_x), Just(min_y), min_x + 2..=width, min_y + 2..=height)
+            });
+            (Just(grid), corners)
+        })
+    ) {
+        let (min_x, min_y, max_x, max_y) = corners;
+        let copied = heights
+            .chunks_exact(dimesions.0)
+            .skip(min_y)
+            .take(max_y - min_y)
+            .flat_map(|row| &row[min_x..max_x])
+            .copied()
+            .collect::<Vec<f64>>();
+
+        let expected = triangulate(&copied, (max_x - min_x, max_y - min_y), Error(max_error)).unwrap();
+        let region = triangulate_region(&heights, dimesions, corners, Error(max_error), false).unwrap();
+        prop_assert_eq!(region, expected);
+    }
+}
@@ -0,0 +1,172 @@
+use std::error::Error as _;
+use std::io::{self, Cursor, Read};
+
+use delatin::{triangulate, triangulate_from_reader, Error, Mesh, TriangulationError};
+
+fn encode(heights: &[f64]) -> Vec<u8> {
+    heights
+        .iter()
+        .flat_map(|height| height.to_le_bytes())
+        .collect()
+}
+
+/// Triangulates the stream in bands and checks that the mesh has no duplicated vertices and
+/// that edges without a twin lie only on the border of the grid, so bands join without cracks.
+fn banded_mesh(heights: &[f64], (width, height): (usize, usize), max_error: Error) -> Mesh {
+    let (points, triangles) =
+        triangulate_from_reader(Cursor::new(encode(heights)), width, max_error).unwrap();
+    let mesh = Mesh::from_triangles(points, &triangles);
+    assert_eq!(mesh.validate(), Ok(()));
+
+    let mut sorted_points = mesh.points().to_vec();
+    sorted_points.sort_unstable();
+    sorted_points.dedup();
+    assert_eq!(sorted_points.len(), mesh.points().len());
+
+    let vertices = mesh.triangles_flat();
+    for (half_edge, twin) in mesh.half_edges().iter().enumerate() {
+        let next = half_edge - half_edge % 3 + (half_edge + 1) % 3;
+        let [start, end] = [half_edge, next].map(|half_edge| mesh.points()[vertices[half_edge]]);
+        let is_border = start.0 == end.0 && (start.0 == 0 || start.0 == width - 1)
+            || start.1 == end.1 && (start.1 == 0 || start.1 == height - 1);
+        assert_eq!(twin.is_none(), is_border, "{start:?} {end:?}");
+    }
+
+    mesh
+}
+
+/// Hands out at most `chunk` bytes per read, interrupted before every read, and fails with a
+/// broken pipe once `fail_after` bytes were read.
+struct FlakyReader {
+    bytes: Cursor<Vec<u8>>,
+    chunk: usize,
+    fail_after: Option<usize>,
+    interrupted: bool,
+}
+
+impl Read for FlakyReader {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.interrupted = !self.interrupted;
+        if self.interrupted {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        if self
+            .fail_after
+            .is_some_and(|fail_after| self.bytes.position() as usize >= fail_after)
+        {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "stream closed"));
+        }
+        let length = buffer.len().min(self.chunk);
+        self.bytes.read(&mut buffer[..length])
+    }
+}
+
+#[test]
+fn chunked_and_interrupted_stream_gives_the_same_mesh() {
+    let heights = (0..9 * 7)
+        .map(|i| ((i * 5) % 11) as f64)
+        .collect::<Vec<f64>>();
+    let reader = FlakyReader {
+        bytes: Cursor::new(encode(&heights)),
+        chunk: 3,
+        fail_after: None,
+        interrupted: false,
+    };
+
+    assert_eq!(
+        triangulate_from_reader(reader, 9, Error(0.5)).unwrap(),
+        triangulate(&heights, (9, 7), Error(0.5)).unwrap()
+    );
+}
+
+#[test]
+fn tall_stream_is_within_max_error() {
+    let (width, height) = (33, 600);
+    let heights = (0..width * height)
+        .map(|i| ((i % width) as f64 / 5.0).sin() * ((i / width) as f64 / 7.0).cos() * 50.0)
+        .collect::<Vec<f64>>();
+
+    let mesh = banded_mesh(&heights, (width, height), Error(0.5));
+    let (all_at_once, _) = triangulate(&heights, (width, height), Error(0.5)).unwrap();
+
+    assert!(mesh.points().iter().any(|point| point.1 == 256));
+    assert!(mesh
+        .error_map(&heights, width, height)
+        .iter()
+        .all(|error| *error <= 0.5));
+    assert!(mesh.points().len() < all_at_once.len() * 2);
+}
+
+#[test]
+fn rounding_errors_on_the_shared_row_make_no_cracks() {
+    // exact refinement of a plane, which later bands see with different rounding
+    let (width, height) = (33, 600);
+    let heights = (0..width * height)
+        .map(|i| (i % width) as f64 * 0.1 + (i / width) as f64 * 0.3)
+        .collect::<Vec<f64>>();
+
+    banded_mesh(&heights, (width, height), Error(0.0));
+}
+
+#[test]
+fn non_finite_height_of_later_band_has_grid_index() {
+    let mut heights = vec![1.0; 4 * 300];
+    heights[4 * 280 + 2] = f64::NAN;
+
+    let result = triangulate_from_reader(Cursor::new(encode(&heights)), 4, Error(0.5));
+    assert!(matches!(
+        result,
+        Err(TriangulationError::NonFiniteHeight { index }) if index == 4 * 280 + 2
+    ));
+}
+
+#[test]
+fn short_stream_is_too_small() {
+    let empty = triangulate_from_reader(Cursor::new(Vec::new()), 4, Error(0.5));
+    assert!(matches!(
+        empty,
+        Err(TriangulationError::GridTooSmall {
+            width: 4,
+            height: 0
+        })
+    ));
+
+    let single_row = triangulate_from_reader(Cursor::new(encode(&[1.0; 4])), 4, Error(0.5));
+    assert!(matches!(
+        single_row,
+        Err(TriangulationError::GridTooSmall {
+            width: 4,
+            height: 1
+        })
+    ));
+}
+
+#[test]
+fn incomplete_row_is_rejected() {
+    let mut bytes = encode(&[1.0; 10]);
+    bytes.truncate(bytes.len() - 3);
+
+    let result = triangulate_from_reader(Cursor::new(bytes), 4, Error(0.5));
+    assert!(matches!(
+        result,
+        Err(TriangulationError::IncompleteRowError)
+    ));
+}
+
+#[test]
+fn read_error_is_returned() {
+    let reader = FlakyReader {
+        bytes: Cursor::new(encode(&[1.0; 16])),
+        chunk: 8,
+        fail_after: Some(40),
+        interrupted: false,
+    };
+
+    let error = triangulate_from_reader(reader, 4, Error(0.5)).unwrap_err();
+    assert!(matches!(
+        &error,
+        TriangulationError::Io(error) if error.kind() == io::ErrorKind::BrokenPipe
+    ));
+    assert_eq!(error.to_string(), "Failed to read or write data.");
+    assert_eq!(error.source().unwrap().to_string(), "stream closed");
+}
@@ -0,0 +1,140 @@
+use delatin::predicates::signed_area;
+use delatin::{
+    CancellationToken, Error, Mesh, Refiner, RelativeError, TriangulationError,
+    TriangulationOptions,
+};
+
+/// Tile with the same shape of the surface scaled to the range.
+fn tile(range: f64) -> Vec<f64> {
+    (0..33 * 33)
+        .map(|i| ((i % 33) as f64 / 5.0).sin() * ((i / 33) as f64 / 4.0).cos() * range)
+        .collect()
+}
+
+/// Checks that edges between vertices less than a pixel away from the segment connect its
+/// ends, as breaklines are split at pixels next to their midpoints.
+fn follows_segment(
+    points: &[(usize, usize)],
+    triangles: &[(usize, usize, usize)],
+    start: (usize, usize),
+    end: (usize, usize),
+) -> bool {
+    let length = (start.0 as f64 - end.0 as f64).hypot(start.1 as f64 - end.1 as f64);
+    let is_near = |vertex: usize| (signed_area(start, end, points[vertex]) as f64).abs() < length;
+
+    let mut is_reached = vec![false; points.len()];
+    let mut stack = points
+        .iter()
+        .position(|&point| point == start)
+        .into_iter()
+        .collect::<Vec<usize>>();
+    while let Some(vertex) = stack.pop() {
+        if points[vertex] == end {
+            return true;
+        }
+        if std::mem::replace(&mut is_reached[vertex], true) {
+            continue;
+        }
+        for &(a, b, c) in triangles {
+            for (from, to) in [(a, b), (b, c), (c, a), (b, a), (c, b), (a, c)] {
+                if from == vertex && !is_reached[to] && is_near(to) {
+                    stack.push(to);
+                }
+            }
+        }
+    }
+
+    false
+}
+
+#[test]
+fn reset_converts_relative_error_for_each_tile() {
+    let low = tile(10.0);
+    let high = tile(1000.0);
+    let options = TriangulationOptions::new().max_error(RelativeError(0.01));
+    let mut refiner = options.start(&low, 33, 33).unwrap();
+
+    for heights in [&low, &high, &low] {
+        refiner.reset(heights).unwrap();
+        while refiner.step().unwrap() {}
+
+        assert_eq!(refiner.snapshot(), options.run(heights, 33, 33).unwrap());
+    }
+}
+
+#[test]
+fn reset_keeps_seed_points_and_breaklines() {
+    let first = tile(10.0);
+    let second = tile(-40.0);
+    let seed_points = [(3, 30), (20, 7)];
+    let breaklines = [((0, 16), (32, 20)), ((5, 0), (9, 32))];
+    let options = TriangulationOptions::new()
+        .max_error(Error(1.0))
+        .seed_points(&seed_points)
+        .breaklines(&breaklines)
+        .preserve_extrema(true);
+    let mut refiner = options.start(&first, 33, 33).unwrap();
+    while refiner.step().unwrap() {}
+
+    refiner.reset(&second).unwrap();
+    while refiner.step().unwrap() {}
+
+    let (points, triangles) = refiner.snapshot();
+    for seed_point in seed_points {
+        assert!(points.contains(&seed_point));
+    }
+    for (start, end) in breaklines {
+        assert!(follows_segment(&points, &triangles, start, end));
+    }
+    assert_eq!((points, triangles), options.run(&second, 33, 33).unwrap());
+}
+
+#[test]
+fn step_stops_once_cancelled() {
+    let heights = tile(100.0);
+    let token = CancellationToken::new();
+    let mut refiner = TriangulationOptions::new()
+        .max_error(Error(0.1))
+        .cancellation(token.clone())
+        .start(&heights, 33, 33)
+        .unwrap();
+    for _ in 0..10 {
+        assert!(refiner.step().unwrap());
+    }
+
+    token.cancel();
+    let Err(TriangulationError::Cancelled { partial }) = refiner.step() else {
+        panic!("step is not cancelled");
+    };
+    let (points, triangles) = refiner.snapshot();
+    assert_eq!(partial.points(), points);
+    assert_eq!(partial.triangles(), triangles);
+    assert!(matches!(
+        refiner.step(),
+        Err(TriangulationError::Cancelled { .. })
+    ));
+}
+
+#[test]
+fn meshes_which_could_overflow_indices_are_rejected() {
+    let heights = tile(10.0);
+    let corners = vec![(0, 0), (32, 0), (32, 32), (0, 32)];
+    let is_rejected = |mesh: &Mesh| {
+        matches!(
+            Refiner::from_mesh(&heights, (33, 33), mesh),
+            Err(TriangulationError::InvalidMeshError)
+        )
+    };
+
+    let mesh = Mesh::from_triangles(corners.clone(), &[(0, 2, 1), (0, 3, 2)]);
+    assert!(!is_rejected(&mesh));
+
+    // refinement of duplicate points or stacked triangles isn't bound by the grid size
+    let mut duplicated = corners.clone();
+    duplicated.push((0, 0));
+    assert!(is_rejected(&Mesh::from_triangles(
+        duplicated,
+        &[(0, 2, 1), (0, 3, 2)]
+    )));
+    assert!(is_rejected(&Mesh::from_triangles(corners, &[(0, 2, 1); 9])));
+}
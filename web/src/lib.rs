@@ -0,0 +1,2 @@
+// exports of `delatin::wasm` are linked into this module
+pub use delatin::wasm::*;